@@ -14,7 +14,6 @@ fn main() {
         .target(obj2)
         .build()
         .unwrap();
-    let diff = diff.compare();
 
-    serde_json::to_writer_pretty(std::io::stdout(), &diff).unwrap();
+    diff.compare_to_writer(std::io::stdout(), true).unwrap();
 }
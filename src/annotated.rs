@@ -0,0 +1,421 @@
+use serde::ser::{SerializeMap, SerializeSeq, SerializeStruct};
+use serde::{Serialize, Serializer};
+use crate::json_patch::json_pointer;
+use crate::{
+    ArrayDifference, ArrayIndex, ArrayOp, Difference, EntryDifference, KeyedPair, Map, MovedElement, Path, PathElement,
+    ScalarDifference,
+};
+
+/// A view of a [`Difference`] that is serialized the same way, but with a `path` field holding
+/// the [RFC 6901](https://datatracker.ietf.org/doc/html/rfc6901) JSON Pointer of every difference,
+/// see [`Difference::with_paths`].
+#[derive(Debug, Clone, Copy)]
+pub struct WithPaths<'a>(&'a Difference);
+
+impl Difference {
+    /// Returns a view of the difference that adds the absolute `path` of the compared values
+    /// to every difference, missing or extra entry and moved element when serialized,
+    /// so consumers can index the changes by their pointer. It's serialized directly,
+    /// so it works with any serializer, see also [`DiffBuilder::annotate_paths`](crate::DiffBuilder::annotate_paths).
+    ///
+    /// Paths of array elements follow the same rules as [`Difference::flatten`]. Missing and extra
+    /// elements of arrays are serialized as is, their index is the key or the offset of the element
+    /// from the `path` of the array.
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    /// use sjdiff::DiffBuilder;
+    ///
+    /// let diff = DiffBuilder::default()
+    ///     .source(json!({"users": [{"age": 31}]}))
+    ///     .target(json!({"users": [{"age": 33}]}))
+    ///     .build()
+    ///     .unwrap()
+    ///     .compare()
+    ///     .unwrap();
+    ///
+    /// let value = serde_json::to_value(diff.with_paths()).unwrap();
+    /// let age = &value["different_entries"]["users"]["value_diff"]["different_pairs"]["0"]["different_entries"]["age"];
+    /// assert_eq!(age["value_diff"]["path"], json!("/users/0/age"));
+    /// ```
+    pub fn with_paths(&self) -> WithPaths<'_> {
+        WithPaths(self)
+    }
+}
+
+impl Serialize for WithPaths<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        AtPath { value: self.0, path: Path::default() }.serialize(serializer)
+    }
+}
+
+/// A part of a difference that is serialized with the paths of the differences it has.
+/// The fields mirror the derived `Serialize` of the difference types, the `path` field comes last.
+struct AtPath<'a, T: ?Sized> {
+    value: &'a T,
+    path: Path,
+}
+
+impl<'a, T: ?Sized> AtPath<'a, T> {
+    /// Returns `value` at the path element `element` of this path.
+    fn nested<U: ?Sized>(&self, element: PathElement, value: &'a U) -> AtPath<'a, U> {
+        let mut path = self.path.clone();
+        path.push(element);
+        AtPath { value, path }
+    }
+
+    fn element<U: ?Sized>(&self, index: usize, value: &'a U) -> AtPath<'a, U> {
+        self.nested(PathElement::ArrayIndex(ArrayIndex::Index(index)), value)
+    }
+
+    fn pointer(&self) -> String {
+        json_pointer(&self.path)
+    }
+}
+
+impl Serialize for AtPath<'_, Difference> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self.value {
+            Difference::Scalar(scalar) => {
+                let mut state = serializer.serialize_struct("Difference", 5)?;
+                state.serialize_field("difference_of", "scalar")?;
+                match scalar {
+                    ScalarDifference::Bool { source, target } => {
+                        state.serialize_field("source", source)?;
+                        state.serialize_field("target", target)?;
+                    }
+                    ScalarDifference::String { source, target, edits } => {
+                        state.serialize_field("source", source)?;
+                        state.serialize_field("target", target)?;
+                        match edits {
+                            Some(edits) => state.serialize_field("edits", edits)?,
+                            None => state.skip_field("edits")?,
+                        }
+                    }
+                    ScalarDifference::Number { source, target, number_kind } => {
+                        state.serialize_field("source", source)?;
+                        state.serialize_field("target", target)?;
+                        state.serialize_field("number_kind", number_kind)?;
+                    }
+                }
+                state.serialize_field("path", &self.pointer())?;
+                state.end()
+            }
+            Difference::Type { source_type, source_value, target_type, target_value } => {
+                let mut state = serializer.serialize_struct("Difference", 6)?;
+                state.serialize_field("difference_of", "type")?;
+                state.serialize_field("source_type", source_type)?;
+                state.serialize_field("source_value", source_value)?;
+                state.serialize_field("target_type", target_type)?;
+                state.serialize_field("target_value", target_value)?;
+                state.serialize_field("path", &self.pointer())?;
+                state.end()
+            }
+            Difference::Array(array) => {
+                let mut state = serializer.serialize_struct("Difference", 8)?;
+                state.serialize_field("difference_of", "array")?;
+                self.serialize_array(array, &mut state)?;
+                state.serialize_field("path", &self.pointer())?;
+                state.end()
+            }
+            Difference::Object { different_entries, truncated_count, key_order } => {
+                let mut state = serializer.serialize_struct("Difference", 5)?;
+                state.serialize_field("difference_of", "object")?;
+                state.serialize_field("different_entries", &AtPath { value: different_entries, path: self.path.clone() })?;
+                match truncated_count {
+                    0 => state.skip_field("truncated_count")?,
+                    _ => state.serialize_field("truncated_count", truncated_count)?,
+                }
+                match key_order {
+                    Some(key_order) => state.serialize_field("key_order", key_order)?,
+                    None => state.skip_field("key_order")?,
+                }
+                state.serialize_field("path", &self.pointer())?;
+                state.end()
+            }
+        }
+    }
+}
+
+impl AtPath<'_, Difference> {
+    fn serialize_array<S>(&self, array: &ArrayDifference, state: &mut S) -> Result<(), S::Error>
+    where
+        S: SerializeStruct,
+    {
+        let at_path = |value| AtPath { value, path: self.path.clone() };
+        match array {
+            ArrayDifference::PairsOnly { different_pairs } => {
+                state.serialize_field("array_difference", "pairs_only")?;
+                state.serialize_field("different_pairs", &at_path(different_pairs))?;
+            }
+            ArrayDifference::Shorter { different_pairs, source_length, missing_elements, truncated_count } => {
+                state.serialize_field("array_difference", "shorter")?;
+                state.serialize_field("different_pairs", &different_pairs.as_ref().map(at_path))?;
+                state.serialize_field("source_length", source_length)?;
+                state.serialize_field("missing_elements", missing_elements)?;
+                match truncated_count {
+                    0 => state.skip_field("truncated_count")?,
+                    _ => state.serialize_field("truncated_count", truncated_count)?,
+                }
+            }
+            ArrayDifference::Longer { different_pairs, target_length, extra_length, extra_elements, truncated_count } => {
+                state.serialize_field("array_difference", "longer")?;
+                state.serialize_field("different_pairs", &different_pairs.as_ref().map(at_path))?;
+                state.serialize_field("target_length", target_length)?;
+                state.serialize_field("extra_length", extra_length)?;
+                state.serialize_field("extra_elements", extra_elements)?;
+                match truncated_count {
+                    0 => state.skip_field("truncated_count")?,
+                    _ => state.serialize_field("truncated_count", truncated_count)?,
+                }
+            }
+            ArrayDifference::Keyed { different_pairs, missing_elements, extra_elements } => {
+                state.serialize_field("array_difference", "keyed")?;
+                state.serialize_field("different_pairs", &AtPath { value: different_pairs.as_slice(), path: self.path.clone() })?;
+                state.serialize_field("missing_elements", missing_elements)?;
+                state.serialize_field("extra_elements", extra_elements)?;
+            }
+            ArrayDifference::Aligned { different_pairs, removed_elements, inserted_elements, moved_elements } => {
+                state.serialize_field("array_difference", "aligned")?;
+                state.serialize_field("different_pairs", &at_path(different_pairs))?;
+                state.serialize_field("removed_elements", removed_elements)?;
+                state.serialize_field("inserted_elements", inserted_elements)?;
+                state.serialize_field("moved_elements", &AtPath { value: moved_elements.as_slice(), path: self.path.clone() })?;
+            }
+            ArrayDifference::Ops { ops } => {
+                state.serialize_field("array_difference", "ops")?;
+                state.serialize_field("ops", &AtPath { value: ops.as_slice(), path: self.path.clone() })?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Serialize for AtPath<'_, Map<usize, Difference>> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(self.value.0.len()))?;
+        for (idx, difference) in &self.value.0 {
+            map.serialize_entry(idx, &self.element(*idx, difference))?;
+        }
+        map.end()
+    }
+}
+
+impl Serialize for AtPath<'_, Map<String, EntryDifference>> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(self.value.0.len()))?;
+        for (key, entry) in &self.value.0 {
+            map.serialize_entry(key, &self.nested(PathElement::Key(key.clone()), entry))?;
+        }
+        map.end()
+    }
+}
+
+impl Serialize for AtPath<'_, EntryDifference> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let (kind, value) = match self.value {
+            EntryDifference::Value { value_diff } => {
+                let mut state = serializer.serialize_struct("EntryDifference", 2)?;
+                state.serialize_field("entry_difference", "value")?;
+                state.serialize_field("value_diff", &AtPath { value: value_diff, path: self.path.clone() })?;
+                return state.end();
+            }
+            EntryDifference::Missing { value } => ("missing", value),
+            EntryDifference::Extra { value } => ("extra", value),
+        };
+        let mut state = serializer.serialize_struct("EntryDifference", 3)?;
+        state.serialize_field("entry_difference", kind)?;
+        state.serialize_field("value", value)?;
+        state.serialize_field("path", &self.pointer())?;
+        state.end()
+    }
+}
+
+impl Serialize for AtPath<'_, [KeyedPair]> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut seq = serializer.serialize_seq(Some(self.value.len()))?;
+        for pair in self.value {
+            seq.serialize_element(&self.element(pair.source_index, pair))?;
+        }
+        seq.end()
+    }
+}
+
+impl Serialize for AtPath<'_, KeyedPair> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("KeyedPair", 4)?;
+        state.serialize_field("key", &self.value.key)?;
+        state.serialize_field("source_index", &self.value.source_index)?;
+        state.serialize_field("target_index", &self.value.target_index)?;
+        state.serialize_field("difference", &AtPath { value: &self.value.difference, path: self.path.clone() })?;
+        state.end()
+    }
+}
+
+impl Serialize for AtPath<'_, [MovedElement]> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut seq = serializer.serialize_seq(Some(self.value.len()))?;
+        for moved in self.value {
+            seq.serialize_element(&self.element(moved.source_index, moved))?;
+        }
+        seq.end()
+    }
+}
+
+impl Serialize for AtPath<'_, MovedElement> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("MovedElement", 4)?;
+        state.serialize_field("source_index", &self.value.source_index)?;
+        state.serialize_field("target_index", &self.value.target_index)?;
+        state.serialize_field("value", &self.value.value)?;
+        state.serialize_field("path", &self.pointer())?;
+        state.end()
+    }
+}
+
+impl Serialize for AtPath<'_, [ArrayOp]> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut seq = serializer.serialize_seq(Some(self.value.len()))?;
+        for op in self.value {
+            match op {
+                ArrayOp::Keep { .. } => seq.serialize_element(op)?,
+                ArrayOp::Replace { index, .. } | ArrayOp::Insert { index, .. } | ArrayOp::Delete { index, .. } => {
+                    seq.serialize_element(&self.element(*index, op))?;
+                }
+            }
+        }
+        seq.end()
+    }
+}
+
+impl Serialize for AtPath<'_, ArrayOp> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let (op, index, value) = match self.value {
+            ArrayOp::Keep { .. } => return self.value.serialize(serializer),
+            ArrayOp::Replace { index, difference } => {
+                let mut state = serializer.serialize_struct("ArrayOp", 3)?;
+                state.serialize_field("op", "replace")?;
+                state.serialize_field("index", index)?;
+                state.serialize_field("difference", &AtPath { value: difference, path: self.path.clone() })?;
+                return state.end();
+            }
+            ArrayOp::Insert { index, value } => ("insert", index, value),
+            ArrayOp::Delete { index, value } => ("delete", index, value),
+        };
+        let mut state = serializer.serialize_struct("ArrayOp", 4)?;
+        state.serialize_field("op", op)?;
+        state.serialize_field("index", index)?;
+        state.serialize_field("value", value)?;
+        state.serialize_field("path", &self.pointer())?;
+        state.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use crate::{ArrayAlignment, ArrayOutput, DiffBuilder};
+
+    fn strip_paths(value: &mut serde_json::Value) {
+        match value {
+            serde_json::Value::Object(map) => {
+                map.remove("path");
+                map.values_mut().for_each(strip_paths);
+            }
+            serde_json::Value::Array(values) => values.iter_mut().for_each(strip_paths),
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn with_paths() {
+        let diff = DiffBuilder::default()
+            .array_key("users", "id")
+            .array_alignment(ArrayAlignment::Lcs)
+            .detect_moved_elements(true)
+            .source(json!({"a/b": 1, "old": 1, "users": [{"id": 1, "age": 31}], "tags": ["x", "y", "z"]}))
+            .target(json!({"a/b": "1", "new": 1, "users": [{"id": 1, "age": 33}], "tags": ["z", "x", "y"]}))
+            .build()
+            .unwrap()
+            .compare()
+            .unwrap();
+
+        let value = serde_json::to_value(diff.with_paths()).unwrap();
+        let entries = &value["different_entries"];
+        assert_eq!(value["path"], json!(""));
+        assert_eq!(entries["a/b"]["value_diff"]["path"], json!("/a~1b"));
+        assert_eq!(entries["old"]["path"], json!("/old"));
+        assert_eq!(entries["new"]["path"], json!("/new"));
+        assert_eq!(entries["users"]["value_diff"]["different_pairs"][0]["difference"]["different_entries"]["age"]["value_diff"]["path"], json!("/users/0/age"));
+        assert_eq!(entries["tags"]["value_diff"]["moved_elements"][0]["path"], json!("/tags/2"));
+
+        let mut plain = serde_json::to_value(&diff).unwrap();
+        assert!(plain.pointer("/different_entries/old/path").is_none());
+        plain["different_entries"]["old"]["path"] = json!("/old");
+        assert_eq!(plain["different_entries"]["old"], entries["old"]);
+    }
+
+    #[test]
+    fn with_paths_mirrors_serialization() {
+        let source = json!({
+            "s": "a b", "n": 1, "b": true, "t": 1, "o": {"x": 1, "y": 2, "z": 3},
+            "short": [1, 2, 3, 4, 5], "long": [1], "keyed": [{"id": 1, "v": 1}, {"id": 2}], "moved": ["x", "y", "z"],
+        });
+        let target = json!({
+            "s": "a c", "n": 2, "b": false, "t": "1", "o": {"x": 2, "y": 3, "z": 4, "w": 1},
+            "short": [0], "long": [2, 3, 4, 5], "keyed": [{"id": 3}, {"id": 1, "v": 2}], "moved": ["z", "x", "y"],
+        });
+        let builders = [
+            DiffBuilder::default()
+                .array_key("keyed", "id")
+                .array_alignment(ArrayAlignment::Lcs)
+                .detect_moved_elements(true)
+                .string_subdiff(true)
+                .clone(),
+            DiffBuilder::default().max_reported_array_elements(1).clone(),
+            DiffBuilder::default().max_entry_differences(2).clone(),
+            DiffBuilder::default().array_output(ArrayOutput::Ops).clone(),
+        ];
+
+        for mut builder in builders {
+            let diff = builder.source(source.clone()).target(target.clone()).build().unwrap().compare().unwrap();
+            let mut annotated = serde_json::to_value(diff.with_paths()).unwrap();
+            strip_paths(&mut annotated);
+            assert_eq!(annotated, serde_json::to_value(&diff).unwrap());
+        }
+    }
+}
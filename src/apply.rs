@@ -0,0 +1,334 @@
+use std::collections::HashSet;
+use std::fmt;
+use crate::json_patch::json_pointer;
+use crate::{ArrayDifference, ArrayIndex, ArrayOp, Difference, EntryDifference, KeyOrder, Map, MovedElement, Path, PathElement, ScalarDifference, Type};
+
+/// An error returned by [`Difference::apply`] when the value no longer has
+/// the shape the difference expects.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApplyError {
+    /// Path to the value that couldn't be patched.
+    pub path: Path,
+    /// Human-readable reason of the failure.
+    pub reason: String,
+}
+
+impl fmt::Display for ApplyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cannot apply difference at '{}': {}", json_pointer(&self.path), self.reason)
+    }
+}
+
+impl std::error::Error for ApplyError {}
+
+impl Difference {
+    /// Patches `source` in place according to the recorded differences,
+    /// so it becomes equal to the `target` the difference was computed against.
+    ///
+    /// An [`ApplyError`] is returned if `source` doesn't have the shape the difference expects,
+    /// e.g. a value has another type or an array index is out of bounds. `source` may be left
+    /// partially patched in that case.
+    ///
+    /// [`ArrayDifference::Shorter`] with truncated missing elements can't be applied,
+    /// see [`DiffBuilder::max_reported_array_elements`](crate::DiffBuilder::max_reported_array_elements),
+    /// neither can [`Difference::Object`] with truncated entries,
+    /// see [`DiffBuilder::max_entry_differences`](crate::DiffBuilder::max_entry_differences),
+    /// nor [`Difference::Type`] with compacted values,
+    /// see [`DiffBuilder::compact_type_diff`](crate::DiffBuilder::compact_type_diff).
+    ///
+    /// Extra elements of [`ArrayDifference::Keyed`] are removed and missing ones are appended
+    /// to the end of the array, so the order of its elements may differ from `target`.
+    pub fn apply(&self, source: &mut serde_json::Value) -> Result<(), ApplyError> {
+        apply_at(self, source, &mut Path::default())
+    }
+}
+
+fn apply_at(difference: &Difference, value: &mut serde_json::Value, path: &mut Path) -> Result<(), ApplyError> {
+    match difference {
+        Difference::Scalar(scalar) => {
+            let expected = match scalar {
+                ScalarDifference::Bool { .. } => Type::Bool,
+                ScalarDifference::String { .. } => Type::String,
+                ScalarDifference::Number { .. } => Type::Number,
+            };
+            expect_type(value, expected, path)?;
+            *value = scalar.target_value();
+        }
+        Difference::Type { source_type, target_type, target_value, .. } => {
+            expect_type(value, *source_type, path)?;
+            if Type::from(target_value) != *target_type {
+                return Err(error(path, "target value is compacted".to_string()));
+            }
+            *value = target_value.clone();
+        }
+        Difference::Array(array) => {
+            expect_type(value, Type::Array, path)?;
+            let elements = value.as_array_mut().unwrap();
+            match array {
+                ArrayDifference::PairsOnly { different_pairs } => {
+                    apply_pairs(different_pairs, elements, path)?;
+                }
+                ArrayDifference::Shorter { different_pairs, source_length, missing_elements, truncated_count } => {
+                    if *truncated_count != 0 {
+                        return Err(error(path, format!("{} missing elements are truncated", truncated_count)));
+                    }
+                    expect_length(elements, *source_length, path)?;
+                    if let Some(different_pairs) = different_pairs {
+                        apply_pairs(different_pairs, elements, path)?;
+                    }
+                    elements.extend(missing_elements.iter().cloned());
+                }
+                ArrayDifference::Longer { different_pairs, target_length, extra_length, .. } => {
+                    expect_length(elements, target_length + extra_length, path)?;
+                    if let Some(different_pairs) = different_pairs {
+                        apply_pairs(different_pairs, elements, path)?;
+                    }
+                    elements.truncate(*target_length);
+                }
+                ArrayDifference::Keyed { different_pairs, missing_elements, extra_elements } => {
+                    let length = elements.len();
+                    for pair in different_pairs {
+                        path.push(PathElement::ArrayIndex(ArrayIndex::Index(pair.source_index)));
+                        let Some(element) = elements.get_mut(pair.source_index) else {
+                            return Err(out_of_bounds(path, length));
+                        };
+                        apply_at(&pair.difference, element, path)?;
+                        path.pop();
+                    }
+                    for (idx, _) in extra_elements.0.iter().rev() {
+                        if *idx >= elements.len() {
+                            path.push(PathElement::ArrayIndex(ArrayIndex::Index(*idx)));
+                            return Err(out_of_bounds(path, length));
+                        }
+                        elements.remove(*idx);
+                    }
+                    elements.extend(missing_elements.0.iter().map(|(_, elem)| elem.clone()));
+                }
+                ArrayDifference::Aligned { different_pairs, removed_elements, inserted_elements, moved_elements } => {
+                    apply_pairs(different_pairs, elements, path)?;
+                    let length = elements.len();
+                    for idx in aligned_removals(removed_elements, moved_elements).into_iter().rev() {
+                        if idx >= elements.len() {
+                            path.push(PathElement::ArrayIndex(ArrayIndex::Index(idx)));
+                            return Err(out_of_bounds(path, length));
+                        }
+                        elements.remove(idx);
+                    }
+                    for (idx, element) in aligned_insertions(inserted_elements, moved_elements) {
+                        if idx > elements.len() {
+                            path.push(PathElement::ArrayIndex(ArrayIndex::Index(idx)));
+                            return Err(out_of_bounds(path, elements.len()));
+                        }
+                        elements.insert(idx, element.clone());
+                    }
+                }
+                ArrayDifference::Ops { ops } => {
+                    let source_length = ops.iter()
+                        .map(|op| match op {
+                            ArrayOp::Keep { count } => *count,
+                            ArrayOp::Replace { .. } | ArrayOp::Delete { .. } => 1,
+                            ArrayOp::Insert { .. } => 0,
+                        })
+                        .sum();
+                    expect_length(elements, source_length, path)?;
+                    let mut source = std::mem::take(elements).into_iter();
+                    for op in ops {
+                        match op {
+                            ArrayOp::Keep { count } => elements.extend(source.by_ref().take(*count)),
+                            ArrayOp::Replace { index, difference } => {
+                                let mut element = source.next().unwrap();
+                                path.push(PathElement::ArrayIndex(ArrayIndex::Index(*index)));
+                                apply_at(difference, &mut element, path)?;
+                                path.pop();
+                                elements.push(element);
+                            }
+                            ArrayOp::Insert { value, .. } => elements.push(value.clone()),
+                            ArrayOp::Delete { .. } => {
+                                source.next();
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        Difference::Object { different_entries, truncated_count, key_order } => {
+            if *truncated_count != 0 {
+                return Err(error(path, format!("{} entry differences are truncated", truncated_count)));
+            }
+            expect_type(value, Type::Object, path)?;
+            let entries = value.as_object_mut().unwrap();
+            for (key, entry) in &different_entries.0 {
+                path.push(PathElement::Key(key.clone()));
+                match entry {
+                    EntryDifference::Missing { value } => {
+                        if entries.contains_key(key) {
+                            return Err(error(path, "entry already exists".to_string()));
+                        }
+                        entries.insert(key.clone(), value.clone());
+                    }
+                    EntryDifference::Extra { .. } => {
+                        if entries.remove(key).is_none() {
+                            return Err(error(path, "entry doesn't exist".to_string()));
+                        }
+                    }
+                    EntryDifference::Value { value_diff } => {
+                        let Some(value) = entries.get_mut(key) else {
+                            return Err(error(path, "entry doesn't exist".to_string()));
+                        };
+                        apply_at(value_diff, value, path)?;
+                    }
+                }
+                path.pop();
+            }
+            if let Some(key_order) = key_order {
+                reorder_keys(entries, key_order, path)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Puts the keys of [`KeyOrder::source`] in the order of [`KeyOrder::target`], the rest of the keys keep their places.
+/// Without the `preserve_order` feature keys are always sorted, so the order stays the same.
+fn reorder_keys(
+    entries: &mut serde_json::Map<String, serde_json::Value>,
+    key_order: &KeyOrder,
+    path: &Path,
+) -> Result<(), ApplyError> {
+    let reordered: HashSet<&String> = key_order.source.iter().collect();
+    let mut target_keys = key_order.target.iter();
+    let keys: Vec<String> = entries.keys()
+        .map(|key| match reordered.contains(key) {
+            true => target_keys.next().unwrap_or(key).clone(),
+            false => key.clone(),
+        })
+        .collect();
+
+    let mut result = serde_json::Map::new();
+    for key in keys {
+        let Some(value) = entries.remove(&key) else {
+            return Err(error(path, format!("entry '{}' to reorder doesn't exist", key)));
+        };
+        result.insert(key, value);
+    }
+    *entries = result;
+
+    Ok(())
+}
+
+fn apply_pairs(
+    pairs: &Map<usize, Difference>,
+    elements: &mut [serde_json::Value],
+    path: &mut Path,
+) -> Result<(), ApplyError> {
+    let length = elements.len();
+    for (idx, difference) in &pairs.0 {
+        path.push(PathElement::ArrayIndex(ArrayIndex::Index(*idx)));
+        let Some(element) = elements.get_mut(*idx) else {
+            return Err(out_of_bounds(path, length));
+        };
+        apply_at(difference, element, path)?;
+        path.pop();
+    }
+
+    Ok(())
+}
+
+/// Returns the sorted `source` indices of the removed and moved elements of [`ArrayDifference::Aligned`].
+pub(crate) fn aligned_removals(removed_elements: &Map<usize, serde_json::Value>, moved_elements: &[MovedElement]) -> Vec<usize> {
+    let mut removals: Vec<_> = removed_elements.0
+        .iter()
+        .map(|(idx, _)| *idx)
+        .chain(moved_elements.iter().map(|moved| moved.source_index))
+        .collect();
+    removals.sort_unstable();
+
+    removals
+}
+
+/// Returns the inserted and moved elements of [`ArrayDifference::Aligned`] sorted by their `target` indices.
+pub(crate) fn aligned_insertions<'a>(
+    inserted_elements: &'a Map<usize, serde_json::Value>,
+    moved_elements: &'a [MovedElement],
+) -> Vec<(usize, &'a serde_json::Value)> {
+    let mut insertions: Vec<_> = inserted_elements.0
+        .iter()
+        .map(|(idx, element)| (*idx, element))
+        .chain(moved_elements.iter().map(|moved| (moved.target_index, &moved.value)))
+        .collect();
+    insertions.sort_by_key(|(idx, _)| *idx);
+
+    insertions
+}
+
+fn expect_type(value: &serde_json::Value, expected: Type, path: &Path) -> Result<(), ApplyError> {
+    let actual = Type::from(value);
+    if actual != expected {
+        return Err(error(path, format!("expected {:?}, found {:?}", expected, actual)));
+    }
+
+    Ok(())
+}
+
+fn expect_length(elements: &[serde_json::Value], expected: usize, path: &Path) -> Result<(), ApplyError> {
+    if elements.len() != expected {
+        return Err(error(path, format!("expected an array of length {}, found {}", expected, elements.len())));
+    }
+
+    Ok(())
+}
+
+fn out_of_bounds(path: &Path, length: usize) -> ApplyError {
+    error(path, format!("index is out of bounds for an array of length {}", length))
+}
+
+fn error(path: &Path, reason: String) -> ApplyError {
+    ApplyError {
+        path: path.clone(),
+        reason,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use crate::{DiffBuilder, Difference};
+
+    fn diff(source: &serde_json::Value, target: &serde_json::Value) -> Difference {
+        let diff = DiffBuilder::default().source(source.clone()).target(target.clone()).build().unwrap();
+        diff.compare().unwrap()
+    }
+
+    #[test]
+    fn apply_reconstructs_target() {
+        let cases = [
+            (json!({"name": "Joe", "age": 31}), json!({"age": 33, "email": "joe@example.com"})),
+            (json!({"users": [{"age": 1}, {"age": 2}]}), json!({"users": [{"age": 1}, {"age": 3}, {"age": 4}]})),
+            (json!({"users": [{"age": 1}, {"age": 2}, {"age": 3}]}), json!({"users": [{"age": 0}]})),
+            (json!({"a": {"b": [true, "x"]}}), json!({"a": {"b": [false, 1]}})),
+            (json!(1), json!("1")),
+        ];
+
+        for (source, target) in cases {
+            let mut patched = source.clone();
+            diff(&source, &target).apply(&mut patched).unwrap();
+            assert_eq!(patched, target);
+        }
+    }
+
+    #[test]
+    fn apply_shape_mismatch() {
+        let source = json!({"users": [{"age": 1}, {"age": 2}]});
+        let target = json!({"users": [{"age": 1}, {"age": 3}]});
+        let diff = diff(&source, &target);
+
+        let mut other = json!({"users": [{"age": 1}]});
+        let err = diff.apply(&mut other).unwrap_err();
+        assert_eq!(err.to_string(), "cannot apply difference at '/users/1': index is out of bounds for an array of length 1");
+
+        let mut other = json!({"users": [{"age": 1}, {"age": "2"}]});
+        let err = diff.apply(&mut other).unwrap_err();
+        assert_eq!(err.to_string(), "cannot apply difference at '/users/1/age': expected Number, found String");
+    }
+}
@@ -58,6 +58,7 @@ fn main() {
                     }
                     _ => return Err("expected array index not all".into())
                 },
+                PathElement::KeyPattern(_) | PathElement::AnyDepth => return Err("expected a key, not a pattern".into()),
             }
         }
 
@@ -70,12 +71,7 @@ fn main() {
     scope.push("source", source);
     scope.push("target", target);
 
-    let path: Path = vec![
-        PathElement::Key("users".to_string()),
-        PathElement::ArrayIndex(ArrayIndex::Index(1)),
-        PathElement::Key("age".to_string()),
-    ]
-        .into();
+    let path: Path = sjdiff::path!["users", 1, "age"];
 
     scope.push("path", path);
 
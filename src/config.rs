@@ -0,0 +1,392 @@
+use serde::Deserialize;
+use crate::{ArrayAlignment, ArrayOutput, CompareError, Diff, DiffBuilder, DiffBuilderError, DiffMode, Difference, DifferenceKind, IgnorePath, Path, Type, WhitespaceMode};
+
+/// The settings of a [`Diff`] without the compared values, so one configuration can be used
+/// for many pairs of values, e.g. a baseline compared with a batch of candidates,
+/// without parsing the paths and cloning the settings again for every pair.
+///
+/// Use [`DiffBuilder::build_config`] to build it.
+///
+/// ```rust
+/// use serde_json::json;
+/// use sjdiff::DiffBuilder;
+///
+/// let config = DiffBuilder::default().ignore_path("id").build_config().unwrap();
+/// let baseline = json!({"id": 1, "name": "Joe"});
+///
+/// assert!(config.compare(&baseline, &json!({"id": 2, "name": "Joe"})).is_none());
+/// assert!(config.compare(&baseline, &json!({"id": 3, "name": "Jim"})).is_some());
+/// ```
+#[derive(Debug, Clone)]
+pub struct DiffConfig {
+    diff: Diff,
+}
+
+/// The schema of [`DiffBuilder::from_config_value`], fields are named after the builder methods.
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigValue {
+    ignore_paths: Vec<IgnorePathValue>,
+    only_paths: Vec<String>,
+    ignore_subtrees: Vec<String>,
+    array_keys: Vec<PathValue<String>>,
+    unordered_arrays: Vec<String>,
+    ignore_path_kinds: Vec<PathValue<DifferenceKind>>,
+    ignore_value_types: Vec<Type>,
+    string_compare_prefixes: Vec<PathValue<usize>>,
+    array_extra_tolerances: Vec<PathValue<usize>>,
+    array_alignment: Option<ArrayAlignment>,
+    array_output: Option<ArrayOutput>,
+    canonicalize_objects: Option<bool>,
+    ordered_object_keys: Option<bool>,
+    mode: Option<DiffMode>,
+    normalize_whitespace: Option<WhitespaceMode>,
+    detect_moved_elements: Option<bool>,
+    equate_empty_arrays: Option<bool>,
+    trim_trailing_nulls: Option<bool>,
+    equate_empty_objects: Option<bool>,
+    equate_null_and_missing: Option<bool>,
+    numbers_equal_across_types: Option<bool>,
+    min_numeric_change: Option<f64>,
+    nan_equals_nan: Option<bool>,
+    coerce_scalars: Option<bool>,
+    parse_numeric_strings: Option<bool>,
+    case_insensitive_strings: Option<bool>,
+    string_subdiff: Option<bool>,
+    compact_type_diff: Option<bool>,
+    sort_output: Option<bool>,
+    max_depth: Option<usize>,
+    max_reported_array_elements: Option<usize>,
+    max_entry_differences: Option<usize>,
+    include_unchanged: Option<bool>,
+    annotate_paths: Option<bool>,
+    #[cfg(feature = "float-approx")]
+    approx_float_eq_epsilon: Option<f64>,
+    #[cfg(feature = "float-approx")]
+    approx_float_eq_epsilons: Vec<PathValue<f64>>,
+    #[cfg(feature = "float-approx")]
+    approx_float_abs_epsilon: Option<f64>,
+    #[cfg(feature = "float-approx")]
+    approx_float_eq_percent: Option<f64>,
+    #[cfg(feature = "datetime")]
+    approx_date_time_eq_millis: Option<u64>,
+    #[cfg(feature = "datetime")]
+    date_time_formats: Option<Vec<String>>,
+    #[cfg(feature = "datetime")]
+    approx_date_eq_days: Option<i64>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum IgnorePathValue {
+    Path(String),
+    WithMissing(IgnorePathWithMissing),
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct IgnorePathWithMissing {
+    path: String,
+    #[serde(default)]
+    ignore_missing: bool,
+}
+
+/// A setting for a path, e.g. `{"path": "users", "value": "id"}` for [`DiffBuilder::array_key`].
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PathValue<T> {
+    path: String,
+    value: T,
+}
+
+impl DiffBuilder {
+    /// Returns a builder with the settings of a JSON document, e.g. a config file of a pipeline
+    /// or a test fixture, instead of calling the builder methods. Other formats like TOML can be
+    /// converted to [`serde_json::Value`] first. Fields are named after the builder methods and all of them
+    /// are optional, unknown fields are rejected. If `value` doesn't match the schema,
+    /// [`DiffBuilderError::Parse`] is returned, paths that can't be parsed are reported by [`DiffBuilder::build`].
+    ///
+    /// - `ignore_paths` – paths or objects like `{"path": "a", "ignore_missing": true}`, see [`DiffBuilder::ignore_path_with_missing`];
+    /// - `only_paths`, `ignore_subtrees`, `unordered_arrays` – paths;
+    /// - `array_keys`, `ignore_path_kinds`, `string_compare_prefixes`, `array_extra_tolerances`, `approx_float_eq_epsilons` – objects
+    ///   with the `path` and the `value` passed to the builder method along with it, e.g. `{"path": "users", "value": "id"}`;
+    /// - `ignore_value_types` – types in snake case, e.g. `"bool"`;
+    /// - `approx_date_time_eq_millis` – [`DiffBuilder::approx_date_time_eq_duration`] in milliseconds;
+    /// - the rest of the settings as they are, enums in snake case, e.g. `"array_alignment": "lcs"`.
+    ///
+    /// Settings of disabled features, e.g. the float tolerances without `float-approx`, are unknown fields.
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    /// use sjdiff::DiffBuilder;
+    ///
+    /// let config = json!({
+    ///     "ignore_paths": ["updated_at", {"path": "meta", "ignore_missing": true}],
+    ///     "array_keys": [{"path": "users", "value": "id"}],
+    ///     "case_insensitive_strings": true
+    /// });
+    ///
+    /// let diff = DiffBuilder::from_config_value(&config)
+    ///     .unwrap()
+    ///     .source(json!({"updated_at": 1, "users": [{"id": 1, "country": "KZ"}, {"id": 2}]}))
+    ///     .target(json!({"updated_at": 2, "users": [{"id": 2}, {"id": 1, "country": "kz"}], "meta": {}}))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// assert!(diff.compare().is_none());
+    /// ```
+    pub fn from_config_value(value: &serde_json::Value) -> Result<DiffBuilder, DiffBuilderError> {
+        let config = ConfigValue::deserialize(value)
+            .map_err(|err| DiffBuilderError::Parse { field: "config", error: err.to_string() })?;
+
+        let mut builder = DiffBuilder::default();
+        for ignore_path in &config.ignore_paths {
+            match ignore_path {
+                IgnorePathValue::Path(path) => builder.ignore_path(path),
+                IgnorePathValue::WithMissing(value) => builder.ignore_path_with_missing(&value.path, value.ignore_missing),
+            };
+        }
+        builder.only_paths(&config.only_paths);
+        for path in &config.ignore_subtrees {
+            builder.ignore_subtree(path);
+        }
+        for array_key in &config.array_keys {
+            builder.array_key(&array_key.path, &array_key.value);
+        }
+        for path in &config.unordered_arrays {
+            builder.unordered_array(path);
+        }
+        for kind in &config.ignore_path_kinds {
+            builder.ignore_path_kind(&kind.path, kind.value);
+        }
+        for value_type in &config.ignore_value_types {
+            builder.ignore_value_type(*value_type);
+        }
+        for prefix in &config.string_compare_prefixes {
+            builder.string_compare_prefix(&prefix.path, prefix.value);
+        }
+        for tolerance in &config.array_extra_tolerances {
+            builder.array_extra_tolerance(&tolerance.path, tolerance.value);
+        }
+
+        // Only the settings that are present replace the defaults of the builder.
+        macro_rules! set {
+            ($($setting:ident),* $(,)?) => {
+                $(if let Some(value) = config.$setting {
+                    builder.$setting(value);
+                })*
+            };
+        }
+        set!(
+            array_alignment, array_output, canonicalize_objects, ordered_object_keys, mode, normalize_whitespace,
+            detect_moved_elements, equate_empty_arrays, trim_trailing_nulls, equate_empty_objects,
+            equate_null_and_missing, numbers_equal_across_types, min_numeric_change, nan_equals_nan, coerce_scalars,
+            parse_numeric_strings, case_insensitive_strings, string_subdiff, compact_type_diff, sort_output, max_depth,
+            max_reported_array_elements, max_entry_differences, include_unchanged, annotate_paths,
+        );
+        #[cfg(feature = "float-approx")]
+        {
+            set!(approx_float_eq_epsilon, approx_float_abs_epsilon, approx_float_eq_percent);
+            for epsilon in &config.approx_float_eq_epsilons {
+                builder.approx_float_eq_epsilon_for_path(&epsilon.path, epsilon.value);
+            }
+        }
+        #[cfg(feature = "datetime")]
+        {
+            set!(date_time_formats, approx_date_eq_days);
+            if let Some(millis) = config.approx_date_time_eq_millis {
+                builder.approx_date_time_eq_duration(std::time::Duration::from_millis(millis));
+            }
+        }
+
+        Ok(builder)
+    }
+
+    /// Builds a [`DiffConfig`] from the settings, `source` and `target` are not used and don't have to be set.
+    pub fn build_config(&self) -> Result<DiffConfig, DiffBuilderError> {
+        let mut builder = self.clone();
+        builder.source(serde_json::Value::Null).target(serde_json::Value::Null);
+        Ok(DiffConfig { diff: builder.build()? })
+    }
+}
+
+impl DiffConfig {
+    /// Compares `source` with `target` using the settings and returns their difference
+    /// or `None` if they are equal, see [`Diff::compare`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the comparison fails, use [`DiffConfig::try_compare`] for untrusted input.
+    pub fn compare(&self, source: &serde_json::Value, target: &serde_json::Value) -> Option<Difference> {
+        match self.try_compare(source, target) {
+            Ok(diff) => diff,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    /// Does the same as [`DiffConfig::compare`], but returns a [`CompareError`] instead of panicking
+    /// when the values can't be compared.
+    ///
+    /// Rhai conditions of the ignore paths have access to the whole values, so the values are cloned
+    /// into a [`Diff`] for them, other settings compare the borrowed values.
+    pub fn try_compare(&self, source: &serde_json::Value, target: &serde_json::Value) -> Result<Option<Difference>, CompareError> {
+        #[cfg(feature = "rhai")]
+        if self.has_rhai_conditions() {
+            let diff = Diff { source: source.clone(), target: target.clone(), ..self.diff.clone() };
+            let difference = diff.try_compare_ref()?;
+            let used = diff.used_ignore_paths.0.into_inner().unwrap();
+            self.diff.used_ignore_paths.0.lock().unwrap().extend(used);
+            return Ok(difference);
+        }
+
+        self.diff.values(&mut Path::default(), source, target)
+    }
+
+    /// Compares `source` with each of `targets`, e.g. a set of golden files with several valid outputs,
+    /// and returns `None` as soon as one of them is equal. Otherwise the index of the closest target
+    /// and the difference with it are returned, the closest one has the fewest differences
+    /// by [`DiffStats::total`](crate::DiffStats::total), the first one wins a tie.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `targets` is empty or the comparison fails, use [`DiffConfig::try_compare_any`] for untrusted input.
+    pub fn compare_any(&self, source: &serde_json::Value, targets: &[serde_json::Value]) -> Option<(usize, Difference)> {
+        match self.try_compare_any(source, targets) {
+            Ok(diff) => diff,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    /// Does the same as [`DiffConfig::compare_any`], but returns a [`CompareError`] instead of panicking
+    /// when the values can't be compared. Returns [`CompareError::NoTargets`] if `targets` is empty.
+    pub fn try_compare_any(
+        &self,
+        source: &serde_json::Value,
+        targets: &[serde_json::Value],
+    ) -> Result<Option<(usize, Difference)>, CompareError> {
+        if targets.is_empty() {
+            return Err(CompareError::NoTargets);
+        }
+
+        let mut closest: Option<(usize, usize, Difference)> = None;
+        for (idx, target) in targets.iter().enumerate() {
+            let Some(difference) = self.try_compare(source, target)? else {
+                return Ok(None);
+            };
+            let total = difference.stats().total();
+            if closest.as_ref().is_none_or(|(_, closest_total, _)| total < *closest_total) {
+                closest = Some((idx, total, difference));
+            }
+        }
+
+        Ok(closest.map(|(idx, _, difference)| (idx, difference)))
+    }
+
+    /// Returns the ignore paths that didn't match any of the values compared so far,
+    /// see [`Diff::unused_ignore_paths`].
+    pub fn unused_ignore_paths(&self) -> Vec<&IgnorePath> {
+        self.diff.unused_ignore_paths()
+    }
+
+    #[cfg(feature = "rhai")]
+    fn has_rhai_conditions(&self) -> bool {
+        self.diff.ignore_paths.iter()
+            .flat_map(|path| &path.conditions)
+            .any(|condition| matches!(condition, crate::IgnorePathCondition::Rhai(_)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use crate::{CompareError, DiffBuilder, DiffBuilderError};
+
+    #[test]
+    fn compare() {
+        let config = DiffBuilder::default()
+            .ignore_path("meta")
+            .ignore_path("unused")
+            .array_key("users", "id")
+            .build_config()
+            .unwrap();
+
+        let baseline = json!({"meta": 1, "users": [{"id": 1, "name": "Joe"}, {"id": 2, "name": "Ann"}]});
+        let candidates = [
+            json!({"meta": 2, "users": [{"id": 2, "name": "Ann"}, {"id": 1, "name": "Joe"}]}),
+            json!({"meta": 3, "users": [{"id": 1, "name": "Jim"}, {"id": 2, "name": "Ann"}]}),
+        ];
+        let texts: Vec<_> = candidates.iter()
+            .map(|candidate| config.compare(&baseline, candidate).map(|difference| difference.to_text()))
+            .collect();
+        assert_eq!(texts, vec![None, Some("~ users.[0].name: \"Joe\" => \"Jim\"".to_string())]);
+
+        let unused: Vec<_> = config.unused_ignore_paths().iter().map(|p| p.path.to_string()).collect();
+        assert_eq!(unused, vec!["unused"]);
+
+        let err = DiffBuilder::default().ignore_path("a.[").build_config().err();
+        assert!(matches!(err, Some(DiffBuilderError::InvalidPath { .. })), "{:?}", err);
+    }
+
+    #[test]
+    fn compare_any() {
+        let config = DiffBuilder::default().ignore_path("id").build_config().unwrap();
+        let source = json!({"id": 1, "name": "Joe", "age": 31, "tags": ["a"]});
+
+        let targets = [
+            json!({"id": 2, "name": "Jim", "age": 33, "tags": ["a"]}),
+            json!({"id": 3, "name": "Joe", "age": 32, "tags": ["a"]}),
+            json!({"id": 4, "name": "Joe", "age": 30, "tags": ["a"]}),
+        ];
+        let (idx, difference) = config.compare_any(&source, &targets).unwrap();
+        assert_eq!(idx, 1);
+        assert_eq!(difference.to_text(), "~ age: 31 => 32");
+
+        let targets = [targets[0].clone(), json!({"id": 5, "name": "Joe", "age": 31, "tags": ["a"]})];
+        assert!(config.compare_any(&source, &targets).is_none());
+        assert_eq!(config.try_compare_any(&source, &[]), Err(CompareError::NoTargets));
+    }
+
+    #[test]
+    fn from_config_value() {
+        let config = json!({
+            "ignore_paths": ["id", {"path": "meta", "ignore_missing": true}],
+            "unordered_arrays": ["tags"],
+            "ignore_path_kinds": [{"path": "code", "value": "type"}],
+            "ignore_value_types": ["bool"],
+            "string_compare_prefixes": [{"path": "hash", "value": 4}],
+            "array_extra_tolerances": [{"path": "logs", "value": 1}],
+            "normalize_whitespace": "trim",
+            "sort_output": true
+        });
+        let diff = DiffBuilder::from_config_value(&config)
+            .unwrap()
+            .source(json!({"id": 1, "tags": ["a", "b"], "code": 1, "flag": true, "hash": "abcdef", "name": " Joe ", "logs": [], "b": 1, "a": 1}))
+            .target(json!({"id": 2, "tags": ["b", "a"], "code": "1", "flag": false, "hash": "abcdxx", "name": "Joe", "meta": {}, "logs": [1], "b": 2, "a": 2}))
+            .build()
+            .unwrap();
+        assert_eq!(diff.compare().unwrap().to_text(), ["~ a: 1 => 2", "~ b: 1 => 2"].join("\n"));
+
+        let err = DiffBuilder::from_config_value(&json!({"ignore_path": ["id"]})).err();
+        assert!(matches!(err, Some(DiffBuilderError::Parse { field: "config", .. })), "{:?}", err);
+        let err = DiffBuilder::from_config_value(&json!({"ignore_paths": [{"path": "a", "ignore_mising": true}]})).err();
+        assert!(matches!(err, Some(DiffBuilderError::Parse { field: "config", .. })), "{:?}", err);
+        let err = DiffBuilder::from_config_value(&json!({"array_alignment": "diagonal"})).err();
+        assert!(matches!(err, Some(DiffBuilderError::Parse { field: "config", .. })), "{:?}", err);
+        let err = DiffBuilder::from_config_value(&json!({"only_paths": ["a.["]})).unwrap().source(json!(1)).target(json!(1)).build().err();
+        assert!(matches!(err, Some(DiffBuilderError::InvalidPath { .. })), "{:?}", err);
+    }
+
+    #[test]
+    #[cfg(feature = "rhai")]
+    fn compare_with_rhai_condition() {
+        let config = DiffBuilder::default()
+            .ignore_path_with_condition("price", crate::IgnorePathCondition::Rhai("target.currency == \"EUR\"".to_string()))
+            .build_config()
+            .unwrap();
+
+        let source = json!({"price": 1, "currency": "EUR"});
+        assert!(config.compare(&source, &json!({"price": 2, "currency": "EUR"})).is_none());
+        assert!(config.compare(&source, &json!({"price": 2, "currency": "USD"})).is_some());
+        assert!(config.unused_ignore_paths().is_empty());
+    }
+}
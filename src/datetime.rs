@@ -0,0 +1,51 @@
+use std::str::FromStr;
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime};
+use crate::{CompareError, Diff, Path};
+
+impl Diff {
+    /// Returns whether the strings are equal as date times or dates within the configured tolerances,
+    /// see [`Diff::approx_date_time_eq_duration`] and [`Diff::approx_date_eq_days`].
+    /// `None` is returned if both tolerances are disabled or the strings aren't both dates.
+    pub(crate) fn dates_eq(&self, curr_path: &Path, source: &str, target: &str) -> Result<Option<bool>, CompareError> {
+        if !self.approx_date_time_eq_duration.is_zero() {
+            let source_datetime = self.parse_date_time(source);
+            let target_datetime = self.parse_date_time(target);
+
+            if let (Some(source_date_time), Some(target_date_time)) = (source_datetime, target_datetime) {
+                let delta = (source_date_time - target_date_time).abs().to_std();
+                let Ok(delta) = delta else {
+                    return Err(CompareError::DateTimeOverflow {
+                        path: curr_path.clone(),
+                        source: source.to_string(),
+                        target: target.to_string(),
+                    });
+                };
+                return Ok(Some(delta <= self.approx_date_time_eq_duration));
+            }
+        }
+        if self.approx_date_eq_days != 0 {
+            let source_date = NaiveDate::from_str(source);
+            let target_date = NaiveDate::from_str(target);
+
+            if let (Ok(source_date), Ok(target_date)) = (source_date, target_date) {
+                return Ok(Some((source_date - target_date).num_days().abs() <= self.approx_date_eq_days));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Parses a string as `rfc3339` date time or using one of [`Diff::date_time_formats`].
+    /// Formats without an offset are treated as UTC.
+    fn parse_date_time(&self, s: &str) -> Option<DateTime<FixedOffset>> {
+        if let Ok(date_time) = DateTime::parse_from_rfc3339(s) {
+            return Some(date_time);
+        }
+
+        self.date_time_formats.iter().find_map(|format| {
+            DateTime::parse_from_str(s, format)
+                .ok()
+                .or_else(|| NaiveDateTime::parse_from_str(s, format).ok().map(|dt| dt.and_utc().fixed_offset()))
+        })
+    }
+}
@@ -1,42 +1,87 @@
-use crate::{ArrayIndex, PathElement};
+use crate::path::PathParseError;
+use crate::{ArrayIndex, KeyPattern, PathElement};
 
-pub(crate) fn parse_element_path(s: &str) -> Result<Vec<PathElement>, String> {
+/// Parses the string path, the offsets of the errors are indices of characters, not bytes.
+pub(crate) fn parse_element_path(s: &str) -> Result<Vec<PathElement>, PathParseError> {
     if s.is_empty() {
-        return Err("Empty path is not allowed".to_string());
+        return Err(error(0, "Empty path is not allowed"));
     }
 
     let mut result = Vec::new();
-    let mut chars = s.chars().peekable();
+    let mut chars = s.chars().enumerate().peekable();
     let mut current = String::new();
     let mut in_quotes = false;
+    // The quote that opened the current quoted segment, `'` or `"`.
+    let mut quote = '\'';
+    let mut in_pattern = false;
     let mut in_brackets = false;
+    // Offsets of the opening quote and bracket, unclosed ones are reported there.
+    let mut quote_offset = 0;
+    let mut bracket_offset = 0;
+    // Set when a quoted key is closed inside brackets, e.g. `["a.b"]`, only `]` may follow it.
+    let mut bracketed_key = false;
 
-    while let Some(c) = chars.next() {
+    while let Some((offset, c)) = chars.next() {
         match c {
-            '\'' => {
+            '~' if !in_quotes && current.is_empty() && matches!(chars.peek(), Some((_, '\'' | '"'))) => {
+                quote = chars.next().unwrap().1;
+                quote_offset = offset;
+                in_quotes = true;
+                in_pattern = true;
+            }
+            '\\' if in_quotes => match chars.next().map(|(_, c)| c) {
+                Some(c) if c == '\\' || c == quote => {
+                    // Patterns keep `\\` as it is an escape of the regular expression as well.
+                    if in_pattern && c == '\\' {
+                        current.push(c);
+                    }
+                    current.push(c);
+                }
+                // Other escapes are left to the regular expression, e.g. `\d`.
+                Some(c) if in_pattern => {
+                    current.push('\\');
+                    current.push(c);
+                }
+                Some(c) => return Err(error(offset, format!("Unknown escape sequence: \\{}", c))),
+                None => return Err(error(quote_offset, "Unclosed quote")),
+            },
+            '\'' | '"' if in_quotes && c != quote => current.push(c),
+            '\'' | '"' => {
                 if in_quotes {
                     if current.is_empty() {
-                        return Err("Empty quoted string is not allowed".to_string());
+                        return Err(error(offset, "Empty quoted string is not allowed"));
+                    }
+                    if in_pattern {
+                        let pattern = KeyPattern::new(&current).map_err(|e| error(offset, format!("Invalid key pattern: {}", e)))?;
+                        result.push(PathElement::KeyPattern(pattern));
+                        in_pattern = false;
+                    } else {
+                        result.push(PathElement::Key(current.clone()));
                     }
-                    result.push(PathElement::Key(current.clone()));
                     current.clear();
                     in_quotes = false;
+                    bracketed_key = in_brackets;
                 } else {
-                    if !current.is_empty() {
-                        return Err("Unexpected quote".to_string());
+                    if !current.is_empty() || bracketed_key {
+                        return Err(error(offset, "Unexpected quote"));
                     }
                     in_quotes = true;
+                    quote = c;
+                    quote_offset = offset;
                 }
             }
+            '.' | '[' if in_brackets && !in_quotes => {
+                return Err(error(offset, format!("Unexpected '{}' in brackets", c)));
+            }
             '.' => {
                 if in_quotes {
                     current.push(c);
                 } else {
                     if !current.is_empty() {
-                        result.push(PathElement::Key(current.clone()));
+                        result.push(unquoted_key(&current));
                         current.clear();
                     } else if result.is_empty() {
-                        return Err("Path cannot start with a dot".to_string());
+                        return Err(error(offset, "Path cannot start with a dot"));
                     }
                 }
             }
@@ -45,28 +90,34 @@ pub(crate) fn parse_element_path(s: &str) -> Result<Vec<PathElement>, String> {
                     current.push(c);
                 } else {
                     if !current.is_empty() {
-                        result.push(PathElement::Key(current.clone()));
+                        result.push(unquoted_key(&current));
                         current.clear();
                     }
                     in_brackets = true;
+                    bracket_offset = offset;
                 }
             }
             ']' => {
                 if in_quotes {
                     current.push(c);
                 } else if in_brackets {
-                    if current == "_" {
+                    if bracketed_key {
+                        if !current.is_empty() {
+                            return Err(error(offset, format!("Unexpected characters after quoted key: {}", current)));
+                        }
+                        bracketed_key = false;
+                    } else if current == "_" {
                         result.push(PathElement::ArrayIndex(ArrayIndex::All));
                     } else {
                         match current.parse::<usize>() {
                             Ok(index) => result.push(PathElement::ArrayIndex(ArrayIndex::Index(index))),
-                            Err(_) => return Err(format!("Invalid array index: {}", current)),
+                            Err(_) => return Err(error(bracket_offset + 1, format!("Invalid array index: {}", current))),
                         }
                     }
                     current.clear();
                     in_brackets = false;
                 } else {
-                    return Err("Unexpected closing bracket".to_string());
+                    return Err(error(offset, "Unexpected closing bracket"));
                 }
             }
             _ => {
@@ -76,27 +127,39 @@ pub(crate) fn parse_element_path(s: &str) -> Result<Vec<PathElement>, String> {
     }
 
     if in_quotes {
-        return Err("Unclosed quote".to_string());
+        return Err(error(quote_offset, "Unclosed quote"));
     }
 
     if in_brackets {
-        return Err("Unclosed bracket".to_string());
+        return Err(error(bracket_offset, "Unclosed bracket"));
     }
 
     if !current.is_empty() {
-        result.push(PathElement::Key(current));
+        result.push(unquoted_key(&current));
     }
 
     if result.is_empty() {
-        return Err("Empty path is not allowed".to_string());
+        return Err(error(s.chars().count(), "Empty path is not allowed"));
     }
 
     Ok(result)
 }
 
+fn error(offset: usize, message: impl Into<String>) -> PathParseError {
+    PathParseError { message: message.into(), offset }
+}
+
+/// Unquoted `**` matches any amount of path elements, quote it to match a key named `**`.
+fn unquoted_key(key: &str) -> PathElement {
+    match key {
+        "**" => PathElement::AnyDepth,
+        _ => PathElement::Key(key.to_string()),
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::{ArrayIndex, PathElement};
+    use crate::{ArrayIndex, KeyPattern, PathElement};
     use super::*;
 
     #[test]
@@ -144,10 +207,93 @@ mod tests {
             ]
         );
 
+        assert_eq!(
+            parse_element_path("a.~'^tmp_.*'.b").unwrap(),
+            vec![
+                PathElement::Key("a".to_string()),
+                PathElement::KeyPattern(KeyPattern::new("^tmp_.*").unwrap()),
+                PathElement::Key("b".to_string())
+            ]
+        );
+
+        assert_eq!(
+            parse_element_path("**.metadata").unwrap(),
+            vec![
+                PathElement::AnyDepth,
+                PathElement::Key("metadata".to_string())
+            ]
+        );
+
+        assert_eq!(
+            parse_element_path("a.'**'").unwrap(),
+            vec![
+                PathElement::Key("a".to_string()),
+                PathElement::Key("**".to_string())
+            ]
+        );
+
+        assert_eq!(
+            parse_element_path("a~'b'").unwrap_err().message,
+            "Unexpected quote"
+        );
+
+        assert_eq!(
+            parse_element_path(r"a.'it\'s'.'[x.y]\\'.b").unwrap(),
+            vec![
+                PathElement::Key("a".to_string()),
+                PathElement::Key("it's".to_string()),
+                PathElement::Key("[x.y]\\".to_string()),
+                PathElement::Key("b".to_string())
+            ]
+        );
+
+        assert_eq!(
+            parse_element_path(r"~'^\d+\'\\$'").unwrap(),
+            vec![PathElement::KeyPattern(KeyPattern::new(r"^\d+'\\$").unwrap())]
+        );
+
+        assert_eq!(
+            parse_element_path(r"'a\n'").unwrap_err().message,
+            r"Unknown escape sequence: \n"
+        );
+
+        assert_eq!(
+            parse_element_path(r#"a."b.c"."it's".~"^x\"".'say "hi"'"#).unwrap(),
+            vec![
+                PathElement::Key("a".to_string()),
+                PathElement::Key("b.c".to_string()),
+                PathElement::Key("it's".to_string()),
+                PathElement::KeyPattern(KeyPattern::new("^x\"").unwrap()),
+                PathElement::Key("say \"hi\"".to_string())
+            ]
+        );
+
+        assert_eq!(
+            parse_element_path(r#"a["foo.bar"].['[1]'].[0]["it\"s"]"#).unwrap(),
+            vec![
+                PathElement::Key("a".to_string()),
+                PathElement::Key("foo.bar".to_string()),
+                PathElement::Key("[1]".to_string()),
+                PathElement::ArrayIndex(ArrayIndex::Index(0)),
+                PathElement::Key("it\"s".to_string())
+            ]
+        );
+
         assert!(parse_element_path("").is_err());
+        assert!(parse_element_path(r#"a["b"c]"#).is_err());
+        assert!(parse_element_path(r#"a["b""c"]"#).is_err());
+        assert!(parse_element_path(r#"a["b"]"#).is_ok());
+        assert!(parse_element_path(r#"a["b""#).is_err());
+        assert!(parse_element_path(r#"a[""]"#).is_err());
+        assert!(parse_element_path("\"\"").is_err());
+        assert!(parse_element_path(r#""a'"#).is_err());
+        assert!(parse_element_path(r#""a\'""#).is_err());
+        assert!(parse_element_path(r"'a\'").is_err());
         assert!(parse_element_path("''").is_err());
         assert!(parse_element_path("a.'").is_err());
         assert!(parse_element_path("a.[").is_err());
         assert!(parse_element_path("a.[x]").is_err());
+        assert!(parse_element_path("~'('").is_err());
+        assert!(parse_element_path("~'a").is_err());
     }
 }
@@ -0,0 +1,155 @@
+use crate::flatten::push_leaves;
+use crate::{CompareError, Diff, Difference, KeyOrder, LeafDifference, Path, ScalarDifference, Type};
+
+/// A single difference reported by [`Diff::compare_with`] as soon as it is found.
+///
+/// Paths of array elements follow the same rules as [`Difference::flatten`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiffEvent {
+    /// Scalar values of the same type are different
+    ScalarChanged {
+        path: Path,
+        difference: ScalarDifference,
+    },
+    /// Values have different types
+    TypeChanged {
+        path: Path,
+        source_type: Type,
+        source_value: serde_json::Value,
+        target_type: Type,
+        target_value: serde_json::Value,
+    },
+    /// An entry or array element from `target` that `source` is missing
+    Missing {
+        path: Path,
+        value: serde_json::Value,
+    },
+    /// An entry or array element that `source` has, and `target` doesn't
+    Extra {
+        path: Path,
+        value: serde_json::Value,
+    },
+    /// An array element that was moved to `target_index`, see [`crate::MovedElement`]
+    Moved {
+        path: Path,
+        target_index: usize,
+        value: serde_json::Value,
+    },
+    /// The keys of an object are in another order, see [`crate::DiffBuilder::ordered_object_keys`]
+    Reordered {
+        path: Path,
+        key_order: KeyOrder,
+    },
+}
+
+impl DiffEvent {
+    /// Returns the full path of the difference from the root.
+    pub fn path(&self) -> &Path {
+        match self {
+            DiffEvent::ScalarChanged { path, .. }
+            | DiffEvent::TypeChanged { path, .. }
+            | DiffEvent::Missing { path, .. }
+            | DiffEvent::Extra { path, .. }
+            | DiffEvent::Moved { path, .. }
+            | DiffEvent::Reordered { path, .. } => path,
+        }
+    }
+
+    fn from_leaf(path: Path, leaf: LeafDifference) -> Self {
+        match leaf {
+            LeafDifference::Scalar(difference) => DiffEvent::ScalarChanged { path, difference: difference.clone() },
+            LeafDifference::Type { source_type, source_value, target_type, target_value } => DiffEvent::TypeChanged {
+                path,
+                source_type,
+                source_value: source_value.clone(),
+                target_type,
+                target_value: target_value.clone(),
+            },
+            LeafDifference::Missing(value) => DiffEvent::Missing { path, value: value.clone() },
+            LeafDifference::Extra(value) => DiffEvent::Extra { path, value: value.clone() },
+            LeafDifference::Moved { target_index, value } => DiffEvent::Moved { path, target_index, value: value.clone() },
+            LeafDifference::Reordered(key_order) => DiffEvent::Reordered { path, key_order: key_order.clone() },
+        }
+    }
+}
+
+impl Diff {
+    /// Compares `source` with `target` and passes every difference to `f` as a [`DiffEvent`]
+    /// as soon as it is found, instead of building the whole [`Difference`] tree in memory.
+    ///
+    /// Events are emitted in the order the values are walked, so the differences of nested values
+    /// come before the missing and extra entries of their object or array. Keyed and LCS aligned
+    /// arrays are compared as a whole before their events are emitted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the comparison fails, use [`Diff::try_compare_with`] for untrusted input.
+    pub fn compare_with<F: FnMut(DiffEvent)>(self, f: F) {
+        if let Err(err) = self.try_compare_with(f) {
+            panic!("{}", err);
+        }
+    }
+
+    /// Does the same as [`Diff::compare_with`], but returns a [`CompareError`] instead of panicking
+    /// when the values can't be compared. Events emitted before the error are not revoked.
+    pub fn try_compare_with<F: FnMut(DiffEvent)>(self, mut f: F) -> Result<(), CompareError> {
+        let mut on_difference = |path: &Path, difference: Difference| {
+            let mut leaves = vec![];
+            push_leaves(&difference, &mut path.clone(), &mut leaves);
+            for (path, leaf) in leaves {
+                f(DiffEvent::from_leaf(path, leaf));
+            }
+        };
+        self.values_with(&mut Path::default(), &self.source, &self.target, Some(&mut on_difference))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use crate::{DiffBuilder, DiffEvent};
+
+    #[test]
+    fn compare_with() {
+        let build = || {
+            DiffBuilder::default()
+                .source(json!({"name": "Joe", "age": 31, "tags": ["a", "b"], "address": {"city": "Paris"}, "extra": 1}))
+                .target(json!({"name": "Joe", "age": "31", "tags": ["a", "c", "d"], "address": {"city": "Rome", "zip": 1}}))
+                .build()
+                .unwrap()
+        };
+
+        let mut events = vec![];
+        build().compare_with(|event| events.push(event));
+
+        let paths: Vec<_> = events.iter().map(|event| event.path().to_string()).collect();
+        assert_eq!(paths, vec!["address.city", "address.zip", "age", "tags.[1]", "tags.[2]", "extra"]);
+        assert!(matches!(events[1], DiffEvent::Missing { .. }));
+        assert!(matches!(events[2], DiffEvent::TypeChanged { .. }));
+        assert!(matches!(events[5], DiffEvent::Extra { .. }));
+
+        let mut expected: Vec<_> = build().compare().unwrap().flatten()
+            .into_iter()
+            .map(|(path, _)| path.to_string())
+            .collect();
+        expected.sort();
+        let mut paths = paths;
+        paths.sort();
+        assert_eq!(paths, expected);
+    }
+
+    #[test]
+    fn compare_with_equal() {
+        let mut events = 0;
+        DiffBuilder::default()
+            .source(json!({"a": [1, {"b": 2}]}))
+            .target(json!({"a": [1, {"b": 2}]}))
+            .build()
+            .unwrap()
+            .compare_with(|_| events += 1);
+
+        assert_eq!(events, 0);
+    }
+}
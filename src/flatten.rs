@@ -0,0 +1,200 @@
+use crate::{ArrayDifference, ArrayIndex, ArrayOp, Difference, EntryDifference, KeyOrder, Map, Path, PathElement, ScalarDifference, Type};
+
+/// A single difference at a leaf of the [`Difference`] tree, see [`Difference::flatten`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LeafDifference<'a> {
+    /// Scalar values of the same type are different
+    Scalar(&'a ScalarDifference),
+    /// Values have different types
+    Type {
+        source_type: Type,
+        source_value: &'a serde_json::Value,
+        target_type: Type,
+        target_value: &'a serde_json::Value,
+    },
+    /// An entry or array element from `target` that `source` is missing
+    Missing(&'a serde_json::Value),
+    /// An entry or array element that `source` has, and `target` doesn't
+    Extra(&'a serde_json::Value),
+    /// An array element that was moved to `target_index`, see [`crate::MovedElement`]
+    Moved {
+        target_index: usize,
+        value: &'a serde_json::Value,
+    },
+    /// The keys of an object are in another order, see [`crate::DiffBuilder::ordered_object_keys`]
+    Reordered(&'a KeyOrder),
+}
+
+impl Difference {
+    /// Returns every leaf difference paired with its full [`Path`] from the root,
+    /// e.g. `users.[1].age` for a changed age of the second user.
+    ///
+    /// Paths of array elements use the index in `source`, except for the elements
+    /// missing in `source`, which use their index in `target`.
+    pub fn flatten(&self) -> Vec<(Path, LeafDifference<'_>)> {
+        let mut leaves = vec![];
+        push_leaves(self, &mut Path::default(), &mut leaves);
+
+        leaves
+    }
+
+    /// Returns the leaf difference with the lexicographically first path, e.g. to find where the values
+    /// start to differ. Keys are compared as strings and array indices as numbers, so `a.[2]` comes
+    /// before `a.[10]` and `b`. Paths follow the rules of [`Difference::flatten`].
+    ///
+    /// `None` is only returned for a difference without leaves, which the comparison never returns.
+    pub fn first_leaf(&self) -> Option<(Path, LeafDifference<'_>)> {
+        self.flatten().into_iter().min_by(|(a, _), (b, _)| a.0.cmp(&b.0))
+    }
+}
+
+pub(crate) fn push_leaves<'a>(difference: &'a Difference, path: &mut Path, leaves: &mut Vec<(Path, LeafDifference<'a>)>) {
+    match difference {
+        Difference::Scalar(scalar) => leaves.push((path.clone(), LeafDifference::Scalar(scalar))),
+        Difference::Type { source_type, source_value, target_type, target_value } => {
+            leaves.push((path.clone(), LeafDifference::Type {
+                source_type: *source_type,
+                source_value,
+                target_type: *target_type,
+                target_value,
+            }));
+        }
+        Difference::Array(array) => match array {
+            ArrayDifference::PairsOnly { different_pairs } => {
+                push_pair_leaves(different_pairs, path, leaves);
+            }
+            ArrayDifference::Shorter { different_pairs, source_length, missing_elements, .. } => {
+                if let Some(different_pairs) = different_pairs {
+                    push_pair_leaves(different_pairs, path, leaves);
+                }
+                for (i, element) in missing_elements.iter().enumerate() {
+                    push_leaf(path, source_length + i, LeafDifference::Missing(element), leaves);
+                }
+            }
+            ArrayDifference::Longer { different_pairs, target_length, extra_elements, .. } => {
+                if let Some(different_pairs) = different_pairs {
+                    push_pair_leaves(different_pairs, path, leaves);
+                }
+                for (i, element) in extra_elements.iter().enumerate() {
+                    push_leaf(path, target_length + i, LeafDifference::Extra(element), leaves);
+                }
+            }
+            ArrayDifference::Keyed { different_pairs, missing_elements, extra_elements } => {
+                for pair in different_pairs {
+                    path.push(PathElement::ArrayIndex(ArrayIndex::Index(pair.source_index)));
+                    push_leaves(&pair.difference, path, leaves);
+                    path.pop();
+                }
+                for (idx, element) in &missing_elements.0 {
+                    push_leaf(path, *idx, LeafDifference::Missing(element), leaves);
+                }
+                for (idx, element) in &extra_elements.0 {
+                    push_leaf(path, *idx, LeafDifference::Extra(element), leaves);
+                }
+            }
+            ArrayDifference::Aligned { different_pairs, removed_elements, inserted_elements, moved_elements } => {
+                push_pair_leaves(different_pairs, path, leaves);
+                for (idx, element) in &removed_elements.0 {
+                    push_leaf(path, *idx, LeafDifference::Extra(element), leaves);
+                }
+                for moved in moved_elements {
+                    let leaf = LeafDifference::Moved { target_index: moved.target_index, value: &moved.value };
+                    push_leaf(path, moved.source_index, leaf, leaves);
+                }
+                for (idx, element) in &inserted_elements.0 {
+                    push_leaf(path, *idx, LeafDifference::Missing(element), leaves);
+                }
+            }
+            ArrayDifference::Ops { ops } => {
+                for op in ops {
+                    match op {
+                        ArrayOp::Keep { .. } => {}
+                        ArrayOp::Replace { index, difference } => {
+                            path.push(PathElement::ArrayIndex(ArrayIndex::Index(*index)));
+                            push_leaves(difference, path, leaves);
+                            path.pop();
+                        }
+                        ArrayOp::Insert { index, value } => push_leaf(path, *index, LeafDifference::Missing(value), leaves),
+                        ArrayOp::Delete { index, value } => push_leaf(path, *index, LeafDifference::Extra(value), leaves),
+                    }
+                }
+            }
+        },
+        Difference::Object { different_entries, key_order, .. } => {
+            if let Some(key_order) = key_order {
+                leaves.push((path.clone(), LeafDifference::Reordered(key_order)));
+            }
+            for (key, entry) in &different_entries.0 {
+                path.push(PathElement::Key(key.clone()));
+                match entry {
+                    EntryDifference::Missing { value } => leaves.push((path.clone(), LeafDifference::Missing(value))),
+                    EntryDifference::Extra { value } => leaves.push((path.clone(), LeafDifference::Extra(value))),
+                    EntryDifference::Value { value_diff } => push_leaves(value_diff, path, leaves),
+                }
+                path.pop();
+            }
+        }
+    }
+}
+
+fn push_pair_leaves<'a>(pairs: &'a Map<usize, Difference>, path: &mut Path, leaves: &mut Vec<(Path, LeafDifference<'a>)>) {
+    for (idx, difference) in &pairs.0 {
+        path.push(PathElement::ArrayIndex(ArrayIndex::Index(*idx)));
+        push_leaves(difference, path, leaves);
+        path.pop();
+    }
+}
+
+fn push_leaf<'a>(path: &mut Path, idx: usize, leaf: LeafDifference<'a>, leaves: &mut Vec<(Path, LeafDifference<'a>)>) {
+    path.push(PathElement::ArrayIndex(ArrayIndex::Index(idx)));
+    leaves.push((path.clone(), leaf));
+    path.pop();
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use crate::{DiffBuilder, LeafDifference, Path};
+
+    #[test]
+    fn flatten() {
+        let diff = DiffBuilder::default()
+            .source(json!({"users": [{"name": "Joe", "age": 31}, {"name": "Ana"}], "tags": ["a"], "id": 1}))
+            .target(json!({"users": [{"name": "Joe", "age": 33}], "tags": ["a", "b"], "id": "1"}))
+            .build()
+            .unwrap()
+            .compare()
+            .unwrap();
+
+        let leaves = diff.flatten();
+        let paths = leaves.iter().map(|(path, _)| path.clone()).collect::<Vec<_>>();
+        let expected: Vec<Path> = ["id", "tags.[1]", "users.[0].age", "users.[1]"]
+            .into_iter()
+            .map(|path| path.parse().unwrap())
+            .collect();
+        assert_eq!(paths, expected);
+
+        assert!(matches!(leaves[0].1, LeafDifference::Type { target_value, .. } if target_value == &json!("1")));
+        assert!(matches!(leaves[1].1, LeafDifference::Missing(value) if value == &json!("b")));
+        assert!(matches!(leaves[2].1, LeafDifference::Scalar(_)));
+        assert!(matches!(leaves[3].1, LeafDifference::Extra(value) if value == &json!({"name": "Ana"})));
+    }
+
+    #[test]
+    fn first_leaf() {
+        let diff = DiffBuilder::default()
+            .source(json!({"b": 1, "a": {"list": [0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 1], "z": 1}}))
+            .target(json!({"b": 2, "a": {"list": [0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 2], "y": 1}}))
+            .build()
+            .unwrap()
+            .compare()
+            .unwrap();
+
+        let (path, leaf) = diff.first_leaf().unwrap();
+        assert_eq!(path, "a.list.[2]".parse().unwrap());
+        assert!(matches!(leaf, LeafDifference::Scalar(_)));
+
+        let diff = DiffBuilder::default().source(json!(1)).target(json!("1")).build().unwrap().compare().unwrap();
+        assert!(matches!(diff.first_leaf(), Some((path, LeafDifference::Type { .. })) if path.is_empty()));
+    }
+}
@@ -0,0 +1,238 @@
+use crate::apply::{aligned_insertions, aligned_removals};
+use crate::{ArrayDifference, ArrayIndex, ArrayOp, Difference, EntryDifference, Map, Path, PathElement};
+
+impl Difference {
+    /// Converts the difference to an [RFC 6902](https://datatracker.ietf.org/doc/html/rfc6902)
+    /// JSON Patch document, which transforms `source` into `target` when applied.
+    ///
+    /// - [`EntryDifference::Missing`] becomes `add`.
+    /// - [`EntryDifference::Extra`] becomes `remove`. Truncated entries are skipped, as they are unknown.
+    /// - [`Difference::Scalar`] and [`Difference::Type`] become `replace`.
+    /// - [`ArrayDifference::Shorter`] appends the missing elements with `add` to the end of the array,
+    ///   and [`ArrayDifference::Longer`] removes the extra elements starting from the last one.
+    ///   Truncated missing elements are not added, as their values are unknown.
+    /// - [`ArrayDifference::Keyed`] patches the matched elements in place, removes the extra ones
+    ///   and appends the missing ones, so the order of the resulting array may differ from `target`.
+    /// - [`ArrayDifference::Aligned`] patches the modified elements in place, removes the removed ones
+    ///   starting from the last one and adds the inserted ones at their indices in `target`.
+    ///   Moved elements are removed and added the same way.
+    /// - [`ArrayDifference::Ops`] becomes the operations in their order at the indices they have
+    ///   in the partially patched array.
+    pub fn to_json_patch(&self) -> Vec<serde_json::Value> {
+        let mut operations = vec![];
+        push_operations(self, &mut Path::default(), &mut operations);
+
+        operations
+    }
+}
+
+fn push_operations(difference: &Difference, path: &mut Path, operations: &mut Vec<serde_json::Value>) {
+    match difference {
+        Difference::Scalar(scalar) => {
+            operations.push(operation("replace", path, Some(scalar.target_value())));
+        }
+        Difference::Type { target_value, .. } => {
+            operations.push(operation("replace", path, Some(target_value.clone())));
+        }
+        Difference::Array(array) => match array {
+            ArrayDifference::PairsOnly { different_pairs } => {
+                push_pair_operations(different_pairs, path, operations);
+            }
+            ArrayDifference::Shorter { different_pairs, missing_elements, .. } => {
+                if let Some(different_pairs) = different_pairs {
+                    push_pair_operations(different_pairs, path, operations);
+                }
+                path.push(PathElement::Key("-".to_string()));
+                for element in missing_elements {
+                    operations.push(operation("add", path, Some(element.clone())));
+                }
+                path.pop();
+            }
+            ArrayDifference::Longer { different_pairs, target_length, extra_length, .. } => {
+                if let Some(different_pairs) = different_pairs {
+                    push_pair_operations(different_pairs, path, operations);
+                }
+                for idx in (*target_length..target_length + extra_length).rev() {
+                    path.push(PathElement::ArrayIndex(ArrayIndex::Index(idx)));
+                    operations.push(operation("remove", path, None));
+                    path.pop();
+                }
+            }
+            ArrayDifference::Keyed { different_pairs, missing_elements, extra_elements } => {
+                for pair in different_pairs {
+                    path.push(PathElement::ArrayIndex(ArrayIndex::Index(pair.source_index)));
+                    push_operations(&pair.difference, path, operations);
+                    path.pop();
+                }
+                for (idx, _) in extra_elements.0.iter().rev() {
+                    path.push(PathElement::ArrayIndex(ArrayIndex::Index(*idx)));
+                    operations.push(operation("remove", path, None));
+                    path.pop();
+                }
+                path.push(PathElement::Key("-".to_string()));
+                for (_, element) in &missing_elements.0 {
+                    operations.push(operation("add", path, Some(element.clone())));
+                }
+                path.pop();
+            }
+            ArrayDifference::Aligned { different_pairs, removed_elements, inserted_elements, moved_elements } => {
+                push_pair_operations(different_pairs, path, operations);
+                for idx in aligned_removals(removed_elements, moved_elements).into_iter().rev() {
+                    path.push(PathElement::ArrayIndex(ArrayIndex::Index(idx)));
+                    operations.push(operation("remove", path, None));
+                    path.pop();
+                }
+                for (idx, element) in aligned_insertions(inserted_elements, moved_elements) {
+                    path.push(PathElement::ArrayIndex(ArrayIndex::Index(idx)));
+                    operations.push(operation("add", path, Some(element.clone())));
+                    path.pop();
+                }
+            }
+            ArrayDifference::Ops { ops } => {
+                // Operations are applied one by one, so `position` is the index in the partially patched array.
+                let mut position = 0;
+                for op in ops {
+                    match op {
+                        ArrayOp::Keep { count } => position += count,
+                        ArrayOp::Replace { difference, .. } => {
+                            path.push(PathElement::ArrayIndex(ArrayIndex::Index(position)));
+                            push_operations(difference, path, operations);
+                            path.pop();
+                            position += 1;
+                        }
+                        ArrayOp::Insert { value, .. } => {
+                            path.push(PathElement::ArrayIndex(ArrayIndex::Index(position)));
+                            operations.push(operation("add", path, Some(value.clone())));
+                            path.pop();
+                            position += 1;
+                        }
+                        ArrayOp::Delete { .. } => {
+                            path.push(PathElement::ArrayIndex(ArrayIndex::Index(position)));
+                            operations.push(operation("remove", path, None));
+                            path.pop();
+                        }
+                    }
+                }
+            }
+        },
+        Difference::Object { different_entries, .. } => {
+            for (key, entry) in &different_entries.0 {
+                path.push(PathElement::Key(key.clone()));
+                match entry {
+                    EntryDifference::Missing { value } => {
+                        operations.push(operation("add", path, Some(value.clone())));
+                    }
+                    EntryDifference::Extra { .. } => {
+                        operations.push(operation("remove", path, None));
+                    }
+                    EntryDifference::Value { value_diff } => {
+                        push_operations(value_diff, path, operations);
+                    }
+                }
+                path.pop();
+            }
+        }
+    }
+}
+
+fn push_pair_operations(pairs: &Map<usize, Difference>, path: &mut Path, operations: &mut Vec<serde_json::Value>) {
+    for (idx, difference) in &pairs.0 {
+        path.push(PathElement::ArrayIndex(ArrayIndex::Index(*idx)));
+        push_operations(difference, path, operations);
+        path.pop();
+    }
+}
+
+fn operation(op: &str, path: &Path, value: Option<serde_json::Value>) -> serde_json::Value {
+    let mut operation = serde_json::Map::new();
+    operation.insert("op".to_string(), op.into());
+    operation.insert("path".to_string(), json_pointer(path).into());
+    if let Some(value) = value {
+        operation.insert("value".to_string(), value);
+    }
+
+    serde_json::Value::Object(operation)
+}
+
+/// Renders the path as an [RFC 6901](https://datatracker.ietf.org/doc/html/rfc6901) JSON Pointer.
+/// `~` and `/` in keys are escaped as `~0` and `~1`.
+pub(crate) fn json_pointer(path: &Path) -> String {
+    let mut pointer = String::new();
+    for elem in path.iter() {
+        pointer.push('/');
+        match elem {
+            PathElement::Key(key) => pointer.push_str(&key.replace('~', "~0").replace('/', "~1")),
+            PathElement::ArrayIndex(ArrayIndex::Index(idx)) => pointer.push_str(&idx.to_string()),
+            PathElement::ArrayIndex(ArrayIndex::All) => pointer.push('-'),
+            PathElement::KeyPattern(pattern) => pointer.push_str(pattern.as_str()),
+            PathElement::AnyDepth => pointer.push_str("**"),
+        }
+    }
+
+    pointer
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use crate::DiffBuilder;
+
+    fn patch(source: serde_json::Value, target: serde_json::Value) -> Vec<serde_json::Value> {
+        let diff = DiffBuilder::default().source(source).target(target).build().unwrap();
+        diff.compare().map(|diff| diff.to_json_patch()).unwrap_or_default()
+    }
+
+    #[test]
+    fn object_entries() {
+        let ops = patch(
+            json!({"name": "Joe", "age": 31, "users": [{"age": 1}, {"age": 2}]}),
+            json!({"age": 33, "email": "joe@example.com", "users": [{"age": 1}, {"age": 3}]}),
+        );
+
+        assert!(ops.contains(&json!({"op": "remove", "path": "/name"})), "{:?}", ops);
+        assert!(ops.contains(&json!({"op": "replace", "path": "/age", "value": 33})), "{:?}", ops);
+        assert!(ops.contains(&json!({"op": "add", "path": "/email", "value": "joe@example.com"})), "{:?}", ops);
+        assert!(ops.contains(&json!({"op": "replace", "path": "/users/1/age", "value": 3})), "{:?}", ops);
+        assert_eq!(ops.len(), 4);
+    }
+
+    #[test]
+    fn array_length() {
+        assert_eq!(
+            patch(json!([1, 2]), json!([1, 3, 4, 5])),
+            vec![
+                json!({"op": "replace", "path": "/1", "value": 3}),
+                json!({"op": "add", "path": "/-", "value": 4}),
+                json!({"op": "add", "path": "/-", "value": 5}),
+            ]
+        );
+
+        assert_eq!(
+            patch(json!([1, 2, 3, 4]), json!([0, 2])),
+            vec![
+                json!({"op": "replace", "path": "/0", "value": 0}),
+                json!({"op": "remove", "path": "/3"}),
+                json!({"op": "remove", "path": "/2"}),
+            ]
+        );
+    }
+
+    #[test]
+    fn type_change_at_root() {
+        assert_eq!(
+            patch(json!({"a": 1}), json!([1])),
+            vec![json!({"op": "replace", "path": "", "value": [1]})]
+        );
+    }
+
+    #[test]
+    fn pointer_escaping() {
+        assert_eq!(
+            patch(json!({"a/b": 1, "m~n": 1}), json!({"a/b": 2, "m~n": 2})),
+            vec![
+                json!({"op": "replace", "path": "/a~1b", "value": 2}),
+                json!({"op": "replace", "path": "/m~0n", "value": 2}),
+            ]
+        );
+    }
+}
@@ -0,0 +1,243 @@
+use std::collections::HashSet;
+use serde_json::json;
+use crate::{ArrayDifference, ArrayOp, Difference, EntryDifference, Map, MovedElement};
+
+impl Difference {
+    /// Converts the difference to a [jsondiffpatch](https://github.com/benjamine/jsondiffpatch) delta,
+    /// so it can be rendered or patched with its tooling.
+    ///
+    /// - [`EntryDifference::Missing`] becomes `[value]`, [`EntryDifference::Extra`] becomes `[value, 0, 0]`.
+    /// - [`Difference::Scalar`] and [`Difference::Type`] become `[source_value, target_value]`.
+    /// - Arrays become objects with the `"_t": "a"` marker. Added and modified elements are keyed by
+    ///   their index in `target`, removed and moved ones by their index in `source` prefixed with `_`,
+    ///   moved elements become `["", target_index, 3]`.
+    /// - Truncated entries and elements are skipped, as their values are unknown.
+    /// - [`ArrayDifference::Keyed`] doesn't record the elements that moved without changes,
+    ///   so they are not reported as moved.
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    /// use sjdiff::DiffBuilder;
+    ///
+    /// let diff = DiffBuilder::default()
+    ///     .source(json!({"name": "Alice", "tags": ["a"]}))
+    ///     .target(json!({"name": "Bob", "tags": ["a", "b"]}))
+    ///     .build()
+    ///     .unwrap()
+    ///     .compare()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(diff.to_jsondiffpatch(), json!({
+    ///     "name": ["Alice", "Bob"],
+    ///     "tags": {"_t": "a", "1": ["b"]},
+    /// }));
+    /// ```
+    pub fn to_jsondiffpatch(&self) -> serde_json::Value {
+        match self {
+            Difference::Scalar(scalar) => json!([scalar.source_value(), scalar.target_value()]),
+            Difference::Type { source_value, target_value, .. } => json!([source_value, target_value]),
+            Difference::Array(array) => array_delta(array),
+            Difference::Object { different_entries, .. } => {
+                let delta = different_entries
+                    .0
+                    .iter()
+                    .map(|(key, entry)| {
+                        let delta = match entry {
+                            EntryDifference::Missing { value } => added(value),
+                            EntryDifference::Extra { value } => deleted(value),
+                            EntryDifference::Value { value_diff } => value_diff.to_jsondiffpatch(),
+                        };
+                        (key.clone(), delta)
+                    })
+                    .collect();
+                serde_json::Value::Object(delta)
+            }
+        }
+    }
+}
+
+fn array_delta(array: &ArrayDifference) -> serde_json::Value {
+    let mut delta = serde_json::Map::new();
+    delta.insert("_t".to_string(), "a".into());
+    match array {
+        ArrayDifference::PairsOnly { different_pairs } => insert_pairs(&mut delta, different_pairs),
+        ArrayDifference::Shorter { different_pairs, source_length, missing_elements, .. } => {
+            if let Some(different_pairs) = different_pairs {
+                insert_pairs(&mut delta, different_pairs);
+            }
+            for (offset, element) in missing_elements.iter().enumerate() {
+                delta.insert((source_length + offset).to_string(), added(element));
+            }
+        }
+        ArrayDifference::Longer { different_pairs, target_length, extra_elements, .. } => {
+            if let Some(different_pairs) = different_pairs {
+                insert_pairs(&mut delta, different_pairs);
+            }
+            for (offset, element) in extra_elements.iter().enumerate() {
+                delta.insert(format!("_{}", target_length + offset), deleted(element));
+            }
+        }
+        ArrayDifference::Keyed { different_pairs, missing_elements, extra_elements } => {
+            for pair in different_pairs {
+                delta.insert(pair.target_index.to_string(), pair.difference.to_jsondiffpatch());
+            }
+            for (idx, element) in &missing_elements.0 {
+                delta.insert(idx.to_string(), added(element));
+            }
+            for (idx, element) in &extra_elements.0 {
+                delta.insert(format!("_{}", idx), deleted(element));
+            }
+        }
+        ArrayDifference::Aligned { different_pairs, removed_elements, inserted_elements, moved_elements } => {
+            for (idx, difference) in aligned_target_indices(different_pairs, removed_elements, inserted_elements, moved_elements) {
+                delta.insert(idx.to_string(), difference.to_jsondiffpatch());
+            }
+            for (idx, element) in &removed_elements.0 {
+                delta.insert(format!("_{}", idx), deleted(element));
+            }
+            for (idx, element) in &inserted_elements.0 {
+                delta.insert(idx.to_string(), added(element));
+            }
+            for moved in moved_elements {
+                delta.insert(format!("_{}", moved.source_index), json!(["", moved.target_index, 3]));
+            }
+        }
+        ArrayDifference::Ops { ops } => {
+            let mut target_index = 0;
+            for op in ops {
+                match op {
+                    ArrayOp::Keep { count } => target_index += count,
+                    ArrayOp::Replace { difference, .. } => {
+                        delta.insert(target_index.to_string(), difference.to_jsondiffpatch());
+                        target_index += 1;
+                    }
+                    ArrayOp::Insert { index, value } => {
+                        delta.insert(index.to_string(), added(value));
+                        target_index += 1;
+                    }
+                    ArrayOp::Delete { index, value } => {
+                        delta.insert(format!("_{}", index), deleted(value));
+                    }
+                }
+            }
+        }
+    }
+
+    serde_json::Value::Object(delta)
+}
+
+fn insert_pairs(delta: &mut serde_json::Map<String, serde_json::Value>, pairs: &Map<usize, Difference>) {
+    for (idx, difference) in &pairs.0 {
+        delta.insert(idx.to_string(), difference.to_jsondiffpatch());
+    }
+}
+
+/// Maps the `source` indices of the modified elements of [`ArrayDifference::Aligned`] to their `target` indices.
+/// Kept and modified elements keep their relative order, so the n-th of them in `source` is the n-th in `target`.
+fn aligned_target_indices<'a>(
+    different_pairs: &'a Map<usize, Difference>,
+    removed_elements: &Map<usize, serde_json::Value>,
+    inserted_elements: &Map<usize, serde_json::Value>,
+    moved_elements: &[MovedElement],
+) -> Vec<(usize, &'a Difference)> {
+    let skipped_targets: HashSet<usize> = inserted_elements.0
+        .iter()
+        .map(|(idx, _)| *idx)
+        .chain(moved_elements.iter().map(|moved| moved.target_index))
+        .collect();
+    let skipped_sources: HashSet<usize> = removed_elements.0
+        .iter()
+        .map(|(idx, _)| *idx)
+        .chain(moved_elements.iter().map(|moved| moved.source_index))
+        .collect();
+
+    let mut pairs = different_pairs.0.iter().peekable();
+    let mut indices = vec![];
+    let mut target_index = 0;
+    let mut source_index = 0;
+    while let Some(&&(idx, ref difference)) = pairs.peek() {
+        if skipped_sources.contains(&source_index) {
+            source_index += 1;
+            continue;
+        }
+        while skipped_targets.contains(&target_index) {
+            target_index += 1;
+        }
+        if idx == source_index {
+            indices.push((target_index, difference));
+            pairs.next();
+        }
+        source_index += 1;
+        target_index += 1;
+    }
+
+    indices
+}
+
+fn added(value: &serde_json::Value) -> serde_json::Value {
+    json!([value])
+}
+
+fn deleted(value: &serde_json::Value) -> serde_json::Value {
+    json!([value, 0, 0])
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use crate::{ArrayAlignment, DiffBuilder};
+
+    #[test]
+    fn to_jsondiffpatch() {
+        let diff = DiffBuilder::default()
+            .array_key("users", "id")
+            .source(json!({
+                "old": 1,
+                "n": 1,
+                "t": 1,
+                "users": [{"id": 1, "age": 31}, {"id": 2}],
+                "list": [1, 2, 3],
+            }))
+            .target(json!({
+                "new": [1],
+                "n": 2,
+                "t": "1",
+                "users": [{"id": 3}, {"id": 1, "age": 33}],
+                "list": [1, 5],
+            }))
+            .build()
+            .unwrap()
+            .compare()
+            .unwrap();
+
+        assert_eq!(diff.to_jsondiffpatch(), json!({
+            "old": [1, 0, 0],
+            "new": [[1]],
+            "n": [1, 2],
+            "t": [1, "1"],
+            "users": {"_t": "a", "1": {"age": [31, 33]}, "0": [{"id": 3}], "_1": [{"id": 2}, 0, 0]},
+            "list": {"_t": "a", "1": [2, 5], "_2": [3, 0, 0]},
+        }));
+    }
+
+    #[test]
+    fn to_jsondiffpatch_aligned() {
+        let diff = DiffBuilder::default()
+            .array_alignment(ArrayAlignment::Lcs)
+            .detect_moved_elements(true)
+            .source(json!(["x", "a", {"v": 1}, "b", "c", "d"]))
+            .target(json!(["a", "n", {"v": 2}, "b", "d", "x"]))
+            .build()
+            .unwrap()
+            .compare()
+            .unwrap();
+
+        assert_eq!(diff.to_jsondiffpatch(), json!({
+            "_t": "a",
+            "1": [{"v": 1}, "n"],
+            "2": [{"v": 2}],
+            "_4": ["c", 0, 0],
+            "_0": ["", 5, 3],
+        }));
+    }
+}
@@ -14,19 +14,65 @@
 //! ```json
 #![doc = include_str!("../examples/simple_object_diff.json")]
 //! ```
+
+mod annotated;
+mod apply;
+mod config;
+#[cfg(feature = "datetime")]
+mod datetime;
 mod element_path_parser;
+mod events;
+mod flatten;
+mod json_patch;
+mod jsondiffpatch;
+mod merge;
+mod merge_patch;
+mod ndjson;
+#[cfg(feature = "rayon")]
+mod parallel;
+pub mod path;
+mod reader;
+mod result;
+mod reverse;
+#[cfg(feature = "rhai")]
 mod rhai_script;
+mod schema;
+mod side_by_side;
+mod stats;
+mod string_edits;
+mod text;
+mod writer;
 
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
 use std::ops::{Deref, DerefMut};
 use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+#[cfg(feature = "datetime")]
 use std::time::Duration;
-use approx::relative_eq;
-use chrono::{DateTime};
-use derive_builder::Builder;
-use serde::{ser::SerializeMap, Serialize};
+#[cfg(feature = "float-approx")]
+use approx::{abs_diff_eq, relative_eq};
+use derive_builder::{Builder, UninitializedFieldError};
+use regex::Regex;
+use serde::{ser::SerializeMap, Deserialize, Serialize};
 use crate::element_path_parser::parse_element_path;
+use crate::json_patch::json_pointer;
+use crate::path::PathParseError;
+use crate::string_edits::string_edits;
+
+pub use crate::annotated::WithPaths;
+pub use crate::apply::ApplyError;
+pub use crate::config::DiffConfig;
+pub use crate::events::DiffEvent;
+pub use crate::flatten::LeafDifference;
+pub use crate::ndjson::NdjsonError;
+pub use crate::result::DiffResult;
+pub use crate::stats::DiffStats;
+pub use crate::string_edits::StringEdit;
+pub use crate::writer::WriteError;
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 #[serde(tag = "entry_difference", rename_all = "snake_case")]
 pub enum EntryDifference {
     /// An entry from `target` that `source` is missing
@@ -37,7 +83,7 @@ pub enum EntryDifference {
     Value { value_diff: Difference },
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Map<K: Serialize, V: Serialize>(pub Vec<(K, V)>);
 
 impl<K: Serialize, V: Serialize> Serialize for Map<K, V> {
@@ -53,7 +99,11 @@ impl<K: Serialize, V: Serialize> Serialize for Map<K, V> {
     }
 }
 
-#[derive(Debug, Serialize)]
+fn is_zero(n: &usize) -> bool {
+    *n == 0
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
 #[serde(tag = "array_difference", rename_all = "snake_case")]
 pub enum ArrayDifference {
     /// `source` and `target` are the same length, but some values of the same indices are different
@@ -65,19 +115,110 @@ pub enum ArrayDifference {
     Shorter {
         /// differing pairs that appear in the overlapping indices of `source` and `target`
         different_pairs: Option<Map<usize, Difference>>,
+        /// The length of `source`, i.e. the index of the first missing element in `target`
+        source_length: usize,
         /// elements missing in `source` that appear in `target`
         missing_elements: Vec<serde_json::Value>,
+        /// The amount of the last missing elements omitted from `missing_elements`,
+        /// see [`DiffBuilder::max_reported_array_elements`]
+        #[serde(skip_serializing_if = "is_zero")]
+        truncated_count: usize,
     },
     /// `source` is longer than `target`
     Longer {
         /// differing pairs that appear in the overlapping indices of `source` and `target`
         different_pairs: Option<Map<usize, Difference>>,
+        /// The length of `target`, i.e. the index of the first extra element in `source`
+        target_length: usize,
         /// The amount of extra elements `source` has that `target` does not
         extra_length: usize,
+        /// extra elements `source` has that `target` does not
+        extra_elements: Vec<serde_json::Value>,
+        /// The amount of the last extra elements omitted from `extra_elements`,
+        /// see [`DiffBuilder::max_reported_array_elements`]
+        #[serde(skip_serializing_if = "is_zero")]
+        truncated_count: usize,
+    },
+    /// Elements of `source` and `target` were matched by the value of a key field,
+    /// see [`DiffBuilder::array_key`], or by their equality regardless of the order,
+    /// see [`DiffBuilder::unordered_array`]
+    Keyed {
+        /// differing pairs of elements that have the same key
+        different_pairs: Vec<KeyedPair>,
+        /// elements missing in `source` whose keys appear only in `target`, by their index in `target`
+        missing_elements: Map<usize, serde_json::Value>,
+        /// elements of `source` whose keys don't appear in `target`, by their index in `source`
+        extra_elements: Map<usize, serde_json::Value>,
+    },
+    /// Elements of `source` and `target` were aligned by their longest common subsequence,
+    /// see [`ArrayAlignment::Lcs`]
+    Aligned {
+        /// modified elements, by their index in `source`
+        different_pairs: Map<usize, Difference>,
+        /// elements that were removed from `source`, by their index in `source`
+        removed_elements: Map<usize, serde_json::Value>,
+        /// elements that were inserted into `target`, by their index in `target`
+        inserted_elements: Map<usize, serde_json::Value>,
+        /// elements that were moved to another index, see [`DiffBuilder::detect_moved_elements`]
+        moved_elements: Vec<MovedElement>,
     },
+    /// The edit script turning `source` into `target`, see [`ArrayOutput::Ops`]
+    Ops {
+        /// operations in the order of the elements, covering both arrays from start to end
+        ops: Vec<ArrayOp>,
+    },
+}
+
+/// An operation of [`ArrayDifference::Ops`]. The operations are applied in order, `Keep`, `Replace` and `Delete`
+/// consume the next elements of `source`, while `Keep`, `Replace` and `Insert` produce the next elements of `target`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum ArrayOp {
+    /// The next `count` elements are equal
+    Keep { count: usize },
+    /// The element at `index` of `source` is modified
+    Replace { index: usize, difference: Difference },
+    /// `value` is inserted at `index` of `target`
+    Insert { index: usize, value: serde_json::Value },
+    /// The element at `index` of `source` is deleted
+    Delete { index: usize, value: serde_json::Value },
+}
+
+/// A pair of array elements matched by the value of their key field.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct KeyedPair {
+    /// The value of the key field both elements have
+    pub key: serde_json::Value,
+    /// Index of the element in `source`
+    pub source_index: usize,
+    /// Index of the element in `target`
+    pub target_index: usize,
+    /// The difference between the elements
+    pub difference: Difference,
+}
+
+/// The keys both objects have in the order of `source` and `target`, see [`DiffBuilder::ordered_object_keys`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct KeyOrder {
+    /// The keys in the order of `source`
+    pub source: Vec<String>,
+    /// The same keys in the order of `target`
+    pub target: Vec<String>,
+}
+
+/// An array element that is equal to an element at another index of the `target` array,
+/// instead of being removed from one index and inserted at another.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MovedElement {
+    /// Index of the element in `source`
+    pub source_index: usize,
+    /// Index of the element in `target`
+    pub target_index: usize,
+    /// The moved element
+    pub value: serde_json::Value,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum Type {
     Null,
@@ -88,7 +229,7 @@ pub enum Type {
     Number,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 #[serde(untagged)]
 pub enum ScalarDifference {
     Bool {
@@ -98,14 +239,81 @@ pub enum ScalarDifference {
     String {
         source: String,
         target: String,
+        /// Edits that turn `source` into `target`, only computed if [`DiffBuilder::string_subdiff`] is set
+        #[serde(skip_serializing_if = "Option::is_none")]
+        edits: Option<Vec<StringEdit>>,
     },
     Number {
         source: serde_json::Number,
         target: serde_json::Number,
+        /// Tells whether the numbers are integers or floats, so a changed representation
+        /// can be told apart from a changed value.
+        number_kind: NumberKind,
     },
 }
 
-#[derive(Debug, Serialize)]
+/// Representations of the numbers of [`ScalarDifference::Number`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NumberKind {
+    /// Both numbers are integers
+    Integer,
+    /// Both numbers are floats
+    Float,
+    /// The number is an integer in `source` and a float in `target`, e.g. `1` and `1.5`
+    IntegerToFloat,
+    /// The number is a float in `source` and an integer in `target`, e.g. `1.5` and `1`
+    FloatToInteger,
+}
+
+impl NumberKind {
+    fn of(source: &serde_json::Number, target: &serde_json::Number) -> Self {
+        match (source.is_f64(), target.is_f64()) {
+            (false, false) => NumberKind::Integer,
+            (true, true) => NumberKind::Float,
+            (false, true) => NumberKind::IntegerToFloat,
+            (true, false) => NumberKind::FloatToInteger,
+        }
+    }
+
+    /// Returns the kind of the numbers with `source` and `target` swapped.
+    pub(crate) fn reverse(self) -> Self {
+        match self {
+            NumberKind::IntegerToFloat => NumberKind::FloatToInteger,
+            NumberKind::FloatToInteger => NumberKind::IntegerToFloat,
+            kind => kind,
+        }
+    }
+}
+
+impl ScalarDifference {
+    /// Returns the difference of two numbers along with their [`NumberKind`].
+    pub(crate) fn number(source: &serde_json::Number, target: &serde_json::Number) -> Self {
+        ScalarDifference::Number {
+            source: source.clone(),
+            target: target.clone(),
+            number_kind: NumberKind::of(source, target),
+        }
+    }
+
+    pub(crate) fn source_value(&self) -> serde_json::Value {
+        match self {
+            ScalarDifference::Bool { source, .. } => serde_json::Value::Bool(*source),
+            ScalarDifference::String { source, .. } => serde_json::Value::String(source.clone()),
+            ScalarDifference::Number { source, .. } => serde_json::Value::Number(source.clone()),
+        }
+    }
+
+    pub(crate) fn target_value(&self) -> serde_json::Value {
+        match self {
+            ScalarDifference::Bool { target, .. } => serde_json::Value::Bool(*target),
+            ScalarDifference::String { target, .. } => serde_json::Value::String(target.clone()),
+            ScalarDifference::Number { target, .. } => serde_json::Value::Number(target.clone()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
 #[serde(tag = "difference_of", rename_all = "snake_case")]
 pub enum Difference {
     Scalar(ScalarDifference),
@@ -118,38 +326,514 @@ pub enum Difference {
     Array(ArrayDifference),
     Object {
         different_entries: Map<String, EntryDifference>,
+        /// The amount of the last different entries omitted from `different_entries`,
+        /// see [`DiffBuilder::max_entry_differences`]
+        #[serde(skip_serializing_if = "is_zero")]
+        truncated_count: usize,
+        /// The order of the keys both objects have if it's different, see [`DiffBuilder::ordered_object_keys`].
+        /// JSON Patch and jsondiffpatch deltas can't express it, so they skip it.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        key_order: Option<KeyOrder>,
+    },
+}
+
+
+/// An error returned by [`Diff::try_compare`] when the values can't be compared.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CompareError {
+    /// A number can't be represented as `f64` to be compared with a float.
+    NumberNotRepresentable {
+        path: Path,
+        number: serde_json::Number,
+    },
+    /// The duration between two date times is out of range.
+    #[cfg(feature = "datetime")]
+    DateTimeOverflow {
+        path: Path,
+        source: String,
+        target: String,
+    },
+    /// Values are nested deeper than [`DiffBuilder::max_depth`] allows.
+    MaxDepthExceeded {
+        path: Path,
+        max_depth: usize,
+    },
+    /// [`DiffConfig::try_compare_any`] was called without targets to compare with.
+    NoTargets,
+}
+
+impl fmt::Display for CompareError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CompareError::NumberNotRepresentable { path, number } => {
+                write!(f, "number {} at '{}' can't be represented as f64", number, json_pointer(path))
+            }
+            #[cfg(feature = "datetime")]
+            CompareError::DateTimeOverflow { path, source, target } => {
+                write!(f, "duration between {} and {} at '{}' is out of range", source, target, json_pointer(path))
+            }
+            CompareError::MaxDepthExceeded { path, max_depth } => {
+                write!(f, "value at '{}' is nested deeper than the max depth of {}", json_pointer(path), max_depth)
+            }
+            CompareError::NoTargets => write!(f, "no targets to compare with"),
+        }
+    }
+}
+
+impl std::error::Error for CompareError {}
+
+/// An error returned by [`DiffBuilder::build`] when [`Diff`] can't be built.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiffBuilderError {
+    /// A required value, `source` or `target`, is not set.
+    UninitializedField(&'static str),
+    /// A path passed to one of the builder methods, e.g. [`DiffBuilder::ignore_path`], can't be parsed.
+    InvalidPath {
+        path: String,
+        error: PathParseError,
+    },
+    /// A value passed to [`DiffBuilder::source_serializable`] or [`DiffBuilder::target_serializable`]
+    /// can't be serialized to JSON.
+    Serialization {
+        field: &'static str,
+        error: String,
+    },
+    /// A value read by [`Diff::from_readers`] or [`DiffBuilder::source_reader`] isn't valid JSON,
+    /// can't be read or has duplicate keys, see [`DiffBuilder::reject_duplicate_keys`],
+    /// a value passed to `DiffBuilder::source_json5` or `DiffBuilder::target_json5` isn't valid JSON5,
+    /// or a value passed to [`DiffBuilder::from_config_value`] doesn't match its schema.
+    Parse {
+        field: &'static str,
+        error: String,
     },
 }
 
+impl fmt::Display for DiffBuilderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiffBuilderError::UninitializedField(field) => write!(f, "{} value is required", field),
+            DiffBuilderError::InvalidPath { path, error } => write!(f, "invalid path '{}': {}", path, error),
+            DiffBuilderError::Serialization { field, error } => write!(f, "{} value can't be serialized: {}", field, error),
+            DiffBuilderError::Parse { field, error } => write!(f, "{} value can't be parsed: {}", field, error),
+        }
+    }
+}
+
+impl std::error::Error for DiffBuilderError {}
+
+impl From<UninitializedFieldError> for DiffBuilderError {
+    fn from(err: UninitializedFieldError) -> Self {
+        DiffBuilderError::UninitializedField(err.field_name())
+    }
+}
+
+/// Defines how the elements of `source` and `target` arrays are aligned before comparison.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArrayAlignment {
+    /// Elements with the same index are compared.
+    #[default]
+    Index,
+    /// Elements are aligned by the longest common subsequence of equal elements, so an
+    /// element inserted near the front doesn't make the rest of the array different.
+    /// Non-matching elements between two equal ones are paired up as modified,
+    /// the rest of them are reported as removed or inserted in [`ArrayDifference::Aligned`].
+    /// It's more expensive as every element of `source` is compared with every element of `target`.
+    Lcs,
+}
+
+/// Defines how the differences of arrays are reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArrayOutput {
+    /// Differences are grouped into modified pairs and the extra or missing elements,
+    /// e.g. [`ArrayDifference::Longer`] or [`ArrayDifference::Aligned`].
+    #[default]
+    Grouped,
+    /// Differences are reported as [`ArrayDifference::Ops`], a sequence of per-index operations, which keeps
+    /// the positions of the inserted and deleted elements relative to the modified ones.
+    /// Arrays compared by index or with [`ArrayAlignment::Lcs`] are reported this way, moved elements become
+    /// a deletion and an insertion. Keyed and unordered arrays match elements regardless of their order,
+    /// so they are still reported as [`ArrayDifference::Keyed`].
+    /// All elements are listed, [`DiffBuilder::max_reported_array_elements`] doesn't apply.
+    Ops,
+}
+
+/// Defines how whitespace in strings is normalized before comparison.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WhitespaceMode {
+    /// Strings are compared as is.
+    #[default]
+    Preserve,
+    /// Leading and trailing whitespace is removed, e.g. `" a  b " == "a  b"`.
+    Trim,
+    /// Leading and trailing whitespace is removed and internal whitespace runs
+    /// are replaced with a single space, e.g. `" a \n b " == "a b"`.
+    Collapse,
+}
+
+/// Defines which kinds of differences are reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffMode {
+    /// All differences are reported.
+    #[default]
+    Full,
+    /// Only changes and removals of the existing values of `source` are reported, so entries
+    /// and array elements added in `target` are not, i.e. `target` may be a superset of `source`.
+    Additive,
+}
+
+/// A kind of difference that can be ignored at specific paths, see [`DiffBuilder::ignore_path_kind`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DifferenceKind {
+    /// [`Difference::Scalar`], scalar values of the same type are different
+    Scalar,
+    /// [`Difference::Type`], values have different types
+    Type,
+    /// [`Difference::Array`], including arrays of different lengths
+    Array,
+    /// [`Difference::Object`]
+    Object,
+    /// [`EntryDifference::Missing`], an object entry that `source` is missing
+    Missing,
+    /// [`EntryDifference::Extra`], an object entry that `target` is missing
+    Extra,
+}
+
+impl Difference {
+    /// Returns the kind of the difference, see [`DifferenceKind`].
+    pub fn kind(&self) -> DifferenceKind {
+        match self {
+            Difference::Scalar(_) => DifferenceKind::Scalar,
+            Difference::Type { .. } => DifferenceKind::Type,
+            Difference::Array(_) => DifferenceKind::Array,
+            Difference::Object { .. } => DifferenceKind::Object,
+        }
+    }
+}
 
 /// Use [`DiffBuilder`] to build [`Diff`] first and run [`Diff::compare`] to get the
 /// difference between two JSON values.
-#[derive(Default, Builder, Debug)]
+///
+/// Both [`Diff`] and [`DiffBuilder`] can be cloned to reuse the settings for other values,
+/// or use [`DiffConfig`] to compare many pairs of values with the same settings.
+/// Custom comparators are shared by the clones, and rhai conditions are kept as scripts
+/// that are compiled on every check, so they don't prevent cloning.
+#[derive(Default, Builder, Debug, Clone)]
+#[builder(build_fn(validate = "Self::validate", error = "DiffBuilderError"))]
 pub struct Diff {
+    /// Only used by the builder to hold the errors of its methods, e.g. paths that failed to parse,
+    /// they are reported by [`DiffBuilder::build`].
+    #[allow(dead_code)]
+    #[builder(setter(custom), field(ty = "Vec<DiffBuilderError>", build = "()"))]
+    errors: (),
+
+    /// Only used by the builder, see [`DiffBuilder::reject_duplicate_keys`].
+    #[allow(dead_code)]
+    #[builder(setter(custom), field(ty = "bool", build = "()"))]
+    reject_duplicate_keys: (),
+
+    /// If true the comparison stops as soon as a difference is found, see [`Diff::equals`].
     #[builder(setter(skip))]
-    #[builder(default = vec![].into())]
-    curr_path: Path,
+    #[builder(default = false)]
+    first_difference_only: bool,
 
     /// An array of paths to ignore.
     /// Use [`DiffBuilder::ignore_path`] to add them in a more convenient way.
     #[builder(default = vec![])]
     ignore_paths: Vec<IgnorePath>,
 
+    /// Indices of the [`Diff::ignore_paths`] that matched any of the compared values,
+    /// see [`Diff::unused_ignore_paths`].
+    #[builder(setter(skip))]
+    #[builder(default)]
+    used_ignore_paths: UsedIgnorePaths,
+
+    /// If true the paths of the scalar values that are equal are recorded, so [`Diff::compare_full`]
+    /// can report what was checked along with what differs in [`DiffResult::unchanged_paths`], e.g. for audit reports.
+    /// Values that are ignored, excluded or compared by custom comparators aren't recorded.
+    /// [`Diff::compare_full`] counts them in [`DiffResult::unchanged_leaves`] either way.
+    /// Other comparisons don't return them, so they don't record them either.
+    #[builder(default = false)]
+    include_unchanged: bool,
+
+    /// If true [`Diff::compare_to_writer`] writes the difference with the `path` of every difference,
+    /// see [`Difference::with_paths`]. [`Difference`] itself doesn't store the paths,
+    /// serialize [`Difference::with_paths`] to annotate other outputs.
+    #[builder(default = false)]
+    annotate_paths: bool,
+
+    /// Set by [`Diff::compare_full`] to record the equal scalar values while comparing.
+    #[builder(setter(skip))]
+    #[builder(default)]
+    record_unchanged: bool,
+
+    /// The equal scalar values, see [`Diff::record_unchanged`].
+    #[builder(setter(skip))]
+    #[builder(default)]
+    unchanged_values: UnchangedValues,
+
+    /// If not empty, only the values at these paths and their descendants are compared,
+    /// everything else is treated as equal.
+    /// Use [`DiffBuilder::only_path`] to add them.
+    #[builder(setter(custom))]
+    #[builder(default = vec![])]
+    only_paths: Vec<Path>,
+
+    /// Paths whose values and descendants are not compared at all.
+    /// Use [`DiffBuilder::ignore_subtree`] to add them.
+    #[builder(setter(custom))]
+    #[builder(default = vec![])]
+    ignored_subtrees: Vec<Path>,
+
+    /// Arrays whose elements should be matched by the value of a key field instead of their index.
+    /// Use [`DiffBuilder::array_key`] to add them.
+    #[builder(setter(custom))]
+    #[builder(default = vec![])]
+    array_keys: Vec<(Path, String)>,
+
+    /// Arrays whose elements are compared as multisets, regardless of their order.
+    /// Use [`DiffBuilder::unordered_array`] to add them.
+    #[builder(setter(custom))]
+    #[builder(default = vec![])]
+    unordered_arrays: Vec<Path>,
+
+    /// Functions that replace the default comparison of the values at specific paths.
+    /// Use [`DiffBuilder::custom_comparator`] to add them.
+    #[builder(setter(custom))]
+    #[builder(default = vec![])]
+    custom_comparators: Vec<CustomComparator>,
+
+    /// Kinds of differences that are not reported at specific paths.
+    /// Use [`DiffBuilder::ignore_path_kind`] to add them.
+    #[builder(setter(custom))]
+    #[builder(default = vec![])]
+    ignored_kinds: Vec<(Path, DifferenceKind)>,
+
+    /// Types of values that are not compared anywhere.
+    /// Use [`DiffBuilder::ignore_value_type`] to add them.
+    #[builder(setter(custom))]
+    #[builder(default = vec![])]
+    ignored_types: Vec<Type>,
+
+    /// If true the values of the key fields of [`DiffBuilder::array_key`] are matched by their canonical JSON
+    /// with sorted object keys, so composite keys like `{"region": "eu", "id": 1}` match regardless of the order
+    /// of their entries, e.g. when `serde_json/preserve_order` is enabled by another crate. Elements of
+    /// [`DiffBuilder::unordered_array`] are compared as values, so the order of the entries never matters there.
+    #[builder(default = false)]
+    canonicalize_objects: bool,
+
+    /// If true the order of object keys is significant, e.g. for ordered maps serialized as JSON objects.
+    /// If the keys both objects have are in a different order, it's reported by [`Difference::Object`]
+    /// along with the different entries. Keys that are ignored, see [`DiffBuilder::ignore_path`], don't count.
+    /// Requires `serde_json/preserve_order` to be enabled, e.g. by the application depending on `serde_json`,
+    /// otherwise the keys are sorted when the values are parsed.
+    #[builder(default = false)]
+    ordered_object_keys: bool,
+
+    /// Defines how array elements are aligned, see [`ArrayAlignment`].
+    #[builder(default = ArrayAlignment::Index)]
+    array_alignment: ArrayAlignment,
+
+    /// Defines how array differences are reported, see [`ArrayOutput`].
+    #[builder(default = ArrayOutput::Grouped)]
+    array_output: ArrayOutput,
+
+    /// If true removed and inserted elements of [`ArrayDifference::Aligned`] that are equal
+    /// are reported as [`MovedElement`]s, so reordering can be told apart from changes of the content.
+    /// Elements of keyed arrays always have both of their indices in [`KeyedPair`].
+    #[builder(default = false)]
+    detect_moved_elements: bool,
+
     /// If true arrays with a length of zero will be equal, regardless of whether they are nil.
+    /// Along with [`DiffBuilder::equate_null_and_missing`] they are also equal to an absent entry.
     #[builder(default = false)]
     equate_empty_arrays: bool,
 
+    /// If true trailing `null` elements of arrays are dropped before they are compared, so `[1, 2, null]`
+    /// and `[1, 2]` are equal, e.g. for serializers that pad arrays. Lengths and indices of the differences refer
+    /// to the trimmed arrays, so `[1, null]` and `[1, 5]` are reported as [`ArrayDifference::Shorter`]
+    /// missing `5` instead of [`ArrayDifference::PairsOnly`] with a type change, while equal-length arrays
+    /// with nulls in the middle still produce [`ArrayDifference::PairsOnly`]. Such differences may not be applicable
+    /// to the untrimmed arrays with [`Difference::apply`]. [`DiffBuilder::equate_empty_arrays`] checks the untrimmed
+    /// arrays, so `[null]` isn't equal to `null`.
+    #[builder(default = false)]
+    trim_trailing_nulls: bool,
+
+    /// If true objects without entries will be equal to `null`, e.g. for serializers
+    /// that emit `null` for empty maps. See [`DiffBuilder::equate_empty_collections`] to set both.
+    #[builder(default = false)]
+    equate_empty_objects: bool,
+
+    /// If true an object entry with a `null` value and an absent entry will be equal,
+    /// so they are not reported as missing or extra. Empty arrays and objects that are equal to `null`
+    /// because of [`DiffBuilder::equate_empty_arrays`] and [`DiffBuilder::equate_empty_objects`]
+    /// are equal to an absent entry as well, so `{"tags": []}` and `{}` are equal with both settings.
+    #[builder(default = false)]
+    equate_null_and_missing: bool,
+
+    /// Defines which kinds of differences are reported, see [`DiffMode`].
+    #[builder(default = DiffMode::Full)]
+    mode: DiffMode,
+
     /// If not zero a float comparison will be done using [`approx::relative_eq`].
     /// It's useful when you want to ignore small differences, e.g. `0.19999999999999 ~ 0.2`.
+    /// The tolerances apply whenever either number is a float, e.g. `2 ~ 2.0001`, while two integers
+    /// are compared exactly unless [`DiffBuilder::numbers_equal_across_types`] is set.
+    #[cfg(feature = "float-approx")]
     #[builder(default = 0.0)]
     approx_float_eq_epsilon: f64,
 
+    /// Epsilons that replace [`DiffBuilder::approx_float_eq_epsilon`] for the numbers at specific paths.
+    /// Use [`DiffBuilder::approx_float_eq_epsilon_for_path`] to add them.
+    #[cfg(feature = "float-approx")]
+    #[builder(setter(custom))]
+    #[builder(default = vec![])]
+    approx_float_eq_epsilons: Vec<(Path, f64)>,
+
+    /// If not zero a float comparison will also be done using [`approx::abs_diff_eq`].
+    /// Unlike [`DiffBuilder::approx_float_eq_epsilon`] it's suitable for values near zero, e.g. `0.0 ~ 0.0001`.
+    #[cfg(feature = "float-approx")]
+    #[builder(default = 0.0)]
+    approx_float_abs_epsilon: f64,
+
+    /// If not zero floats are also equal if they differ by at most this percentage of the larger one
+    /// by absolute value, e.g. `5.0` makes `100.0 ~ 104.0`. It's more intuitive than an epsilon for business metrics.
+    /// Numbers are equal if they pass any of the tolerances, so it neither replaces nor is replaced by
+    /// [`DiffBuilder::approx_float_eq_epsilon`] and the epsilons set for specific paths.
+    #[cfg(feature = "float-approx")]
+    #[builder(default = 0.0)]
+    approx_float_eq_percent: f64,
+
+    /// If true all numbers are compared as `f64` regardless of their JSON representation,
+    /// so `2`, `2.0` and `2.00000` are equal, and the float approximation settings
+    /// apply to integers as well. Otherwise two integers are compared exactly.
+    #[builder(default = false)]
+    numbers_equal_across_types: bool,
+
+    /// If not zero numbers whose absolute difference is less than this value are equal, integers included,
+    /// e.g. `0.5` makes `100 ~ 100.4`, so insignificant deltas are filtered out of reports.
+    /// Unlike the float tolerances, it doesn't depend on the magnitude of the numbers or the `float-approx` feature.
+    #[builder(default = 0.0)]
+    min_numeric_change: f64,
+
+    /// If true NaN numbers are equal to each other, otherwise NaN is never equal to any number.
+    /// Infinities are always equal only to the infinity of the same sign, regardless of the tolerances.
+    /// Numbers parsed by `serde_json` are always finite, so it only matters for the numbers
+    /// that become non-finite when converted to `f64`.
+    #[builder(default = false)]
+    nan_equals_nan: bool,
+
+    /// If true a string is compared with a number or a bool by its parsed value, so `"5"` and `5`,
+    /// or `"true"` and `true` are equal. It's useful for data that round-tripped through a loosely-typed store.
+    /// [`Difference::Type`] is reported if the string can't be parsed or the parsed value is different.
+    #[builder(default = false)]
+    coerce_scalars: bool,
+
+    /// If true a string is compared with a number by its parsed value, so `"42"` and `42` are equal,
+    /// using the number settings like the float tolerances, e.g. for producers
+    /// that quote numbers. Unlike [`DiffBuilder::coerce_scalars`] strings are not compared with bools.
+    /// [`Difference::Type`] is reported if the string can't be parsed or the parsed number is different.
+    /// Array elements are compared the same way, and keyed arrays match a numeric string key
+    /// with the equal number, e.g. `{"id": "1"}` with `{"id": 1}`, see [`DiffBuilder::array_key`].
+    #[builder(default = false)]
+    parse_numeric_strings: bool,
+
     /// An acceptable duration difference for the JSON string values that
     /// are valid timestamps. Date approximation will only be executed
     /// when this value is not zero and a string value is a valid `rfc3339` date.
+    #[cfg(feature = "datetime")]
     #[builder(default = Duration::from_millis(0))]
     approx_date_time_eq_duration: Duration,
 
+    /// Additional [`chrono` formats](chrono::format::strftime) tried in order when a string
+    /// is not a valid `rfc3339` date, e.g. `%Y-%m-%d %H:%M:%S`, `%s` for Unix timestamps
+    /// or `%a, %d %b %Y %H:%M:%S %z` for `rfc2822`. Formats without an offset are treated as UTC.
+    /// If none of them match, strings are compared as is.
+    #[cfg(feature = "datetime")]
+    #[builder(default = vec![])]
+    date_time_formats: Vec<String>,
+
+    /// An acceptable difference in days for the JSON string values that are
+    /// valid dates without time, e.g. `2023-07-25`. Date approximation will only be
+    /// executed when this value is not zero. Full timestamps are handled by
+    /// [`DiffBuilder::approx_date_time_eq_duration`].
+    #[cfg(feature = "datetime")]
+    #[builder(default = 0)]
+    approx_date_eq_days: i64,
+
+    /// If true strings are lowercased before comparison, e.g. `"KZ" == "kz"`.
+    /// Date approximation is done before that, so it's not affected.
+    #[builder(default = false)]
+    case_insensitive_strings: bool,
+
+    /// Defines how whitespace in strings is normalized before comparison, see [`WhitespaceMode`].
+    /// Date approximation is done before that, so it's not affected.
+    #[builder(default = WhitespaceMode::Preserve)]
+    normalize_whitespace: WhitespaceMode,
+
+    /// Numbers of characters that are compared instead of the whole strings at specific paths.
+    /// Use [`DiffBuilder::string_compare_prefix`] to add them.
+    #[builder(setter(custom))]
+    #[builder(default = vec![])]
+    string_compare_prefixes: Vec<(Path, usize)>,
+
+    /// Numbers of elements `target` may have in addition to `source` at specific paths.
+    /// Use [`DiffBuilder::array_extra_tolerance`] to add them.
+    #[builder(setter(custom))]
+    #[builder(default = vec![])]
+    array_extra_tolerances: Vec<(Path, usize)>,
+
+    /// If true differences of strings also carry the edits that turn `source` into `target`, see [`StringEdit`],
+    /// which makes changes of long texts easier to review. Strings are aligned by their words, so it's
+    /// as expensive as multiplying the amounts of words of both strings. If aligning their changed
+    /// parts would take more than 16 MB, e.g. about a thousand words each, they are replaced by a single edit.
+    #[builder(default = false)]
+    string_subdiff: bool,
+
+    /// If true arrays and objects in [`Difference::Type`] whose JSON is longer than 64 bytes are replaced
+    /// by a string with the first 64 bytes of their JSON followed by `...`, e.g. `{"id":1,"name":"Joe",...`,
+    /// while the type names are kept. It keeps the differences small when many values change their type,
+    /// e.g. during a schema migration. Such differences can't be applied by [`Difference::apply`].
+    #[builder(default = false)]
+    compact_type_diff: bool,
+
+    /// If not zero values nested deeper than this amount of path elements are not compared,
+    /// and [`CompareError::MaxDepthExceeded`] is returned instead. It protects against
+    /// overflowing the stack when comparing untrusted input.
+    #[builder(default = 0)]
+    max_depth: usize,
+
+    /// If not zero at most this amount of missing or extra elements is reported
+    /// by [`ArrayDifference::Shorter`] and [`ArrayDifference::Longer`], the rest are only counted
+    /// in their `truncated_count`. It keeps the differences of very long arrays bounded.
+    /// Truncated differences can't be applied with [`Difference::apply`].
+    #[builder(default = 0)]
+    max_reported_array_elements: usize,
+
+    /// If not zero at most this amount of different entries is reported by [`Difference::Object`],
+    /// the rest are only counted in its `truncated_count`. It gives a quick, bounded summary of objects
+    /// that differ in many keys. Entries are truncated after they are sorted, see [`DiffBuilder::sort_output`].
+    /// Truncated differences can't be applied with [`Difference::apply`].
+    #[builder(default = 0)]
+    max_entry_differences: usize,
+
+    /// If true the elements of arrays are compared in parallel using `rayon`, which speeds up
+    /// the comparison of big arrays. The difference is the same as the one compared serially.
+    /// Requires the `rayon` feature, otherwise it has no effect.
+    #[cfg_attr(not(feature = "rayon"), allow(dead_code))]
+    #[builder(default = false)]
+    parallel: bool,
+
+    /// If true the entries of object differences are sorted by their key, otherwise entries of `source`
+    /// come first in their order, followed by the entries missing in it. It makes the output deterministic,
+    /// e.g. for snapshot tests. Pairs of array differences are always sorted by their index.
+    #[builder(default = false)]
+    sort_output: bool,
+
     /// Source JSON value that will be compared with [`Diff::target`].
     source: serde_json::Value,
 
@@ -174,6 +858,16 @@ impl DiffBuilder {
     ///
     /// and `a.[1].c` will ignore `c` key in the element with index 1.
     ///
+    /// `a.~'_at$'` will ignore all keys of `a` matching the regular expression `_at$`,
+    /// see [`KeyPattern`].
+    ///
+    /// Keys with special characters are quoted with `'` or `"`, e.g. `a.'b.c'` or `a."b.c"`.
+    /// Inside quotes a backslash escapes the quote and itself, e.g. `'it\'s'` or `"it's"`,
+    /// other escapes are rejected. Patterns only unescape the quote, e.g. `~"^\d+"`.
+    /// Quoted keys can also be put in brackets like in JSONPath, e.g. `a["b.c"]` is the same as `a.'b.c'`.
+    ///
+    /// `**.metadata` will ignore `metadata` key at any depth, `**` matches zero or more elements.
+    ///
     /// `address.zip` will ignore `zip` key in the `address`:
     ///
     /// ```json
@@ -196,11 +890,23 @@ impl DiffBuilder {
         self.ignore_path_with_missing(path, false)
     }
 
+    /// Does the same as [`DiffBuilder::ignore_path`], but returns the parser error right away
+    /// if `path` is invalid instead of reporting it from [`DiffBuilder::build`].
+    pub fn try_ignore_path(&mut self, path: &str) -> Result<&mut Self, PathParseError> {
+        let ignore_path = IgnorePathBuilder::default()
+            .path(Path::from_str(path)?)
+            .build()
+            .unwrap();
+        self.ignore_paths.get_or_insert_with(Vec::new).push(ignore_path);
+        Ok(self)
+    }
+
     /// Adds a path to the ignored ones. `ignore_missing` indicates whether the element should
     /// be ignored if it's missing in the source or target.
     /// See documentation for [`DiffBuilder::ignore_path`] for usage examples.
+    /// If `path` can't be parsed, [`DiffBuilder::build`] returns [`DiffBuilderError::InvalidPath`].
     pub fn ignore_path_with_missing(&mut self, path: &str, ignore_missing: bool) -> &mut Self {
-        if let Ok(path) = Path::from_str(path) {
+        if let Some(path) = self.parse_path(path) {
             let ignore_path = IgnorePathBuilder::default()
                 .path(path)
                 .ignore_missing(ignore_missing)
@@ -211,10 +917,10 @@ impl DiffBuilder {
         self
     }
 
-    /// Does the same as [`DiffBuilder::ignore_path`] but you can pass a custom script as a condition.
-    /// See the example `ignore_with_rhai_script.rs` to learn how to use it.
+    /// Does the same as [`DiffBuilder::ignore_path`] but the path is ignored only if the condition is met,
+    /// see [`IgnorePathCondition`]. See the example `ignore_with_rhai_script.rs` to learn how to use a custom script.
     pub fn ignore_path_with_condition(&mut self, path: &str, condition: IgnorePathCondition) -> &mut Self {
-        if let Ok(path) = Path::from_str(path) {
+        if let Some(path) = self.parse_path(path) {
             let ignore_path = IgnorePathBuilder::default()
                 .path(path)
                 .condition(condition)
@@ -226,599 +932,3538 @@ impl DiffBuilder {
     }
 }
 
-impl Diff {
-    fn arrays(
-        &mut self,
-        source: Vec<serde_json::Value>,
-        target: Vec<serde_json::Value>,
-    ) -> Option<ArrayDifference> {
-        let different_pairs = self.compare_array_elements(&source, &target);
-        let different_pairs = if different_pairs.is_empty() {
-            None
-        } else {
-            Some(Map(different_pairs))
-        };
-
-        match (source.len(), target.len()) {
-            (s, t) if s > t => Some(ArrayDifference::Longer {
-                different_pairs,
-                extra_length: s - t,
-            }),
-            (s, t) if s < t => Some(ArrayDifference::Shorter {
-                different_pairs,
-                missing_elements: target.into_iter().skip(s).collect(),
-            }),
-            _ => different_pairs.map(|pairs| ArrayDifference::PairsOnly { different_pairs: pairs }),
+impl DiffBuilder {
+    /// Parses `path`, if it's invalid the error is recorded to be returned by [`DiffBuilder::build`].
+    fn parse_path(&mut self, path: &str) -> Option<Path> {
+        match Path::from_str(path) {
+            Ok(parsed) => Some(parsed),
+            Err(error) => {
+                self.errors.push(DiffBuilderError::InvalidPath { path: path.to_string(), error });
+                None
+            }
         }
     }
 
-    fn compare_array_elements(
-        &mut self,
-        source: &[serde_json::Value],
-        target: &[serde_json::Value],
-    ) -> Vec<(usize, Difference)> {
-        let mut iterations = 0;
-        let res: Vec<_> = source
-            .iter()
-            .zip(target.iter())
-            .enumerate()
-            .filter_map(|(i, (s, t))| {
-                iterations += 1;
-                let elem_path = PathElement::ArrayIndex(ArrayIndex::Index(i));
-                if i > 0 { self.curr_path.pop(); }
-                self.curr_path.push(elem_path);
-                self.values(s.clone(), t.clone()).map(|diff| (i, diff))
-            })
-            .collect();
-        if iterations != 0 {
-            self.curr_path.pop();
-        };
+    fn validate(&self) -> Result<(), DiffBuilderError> {
+        match self.errors.first() {
+            Some(err) => Err(err.clone()),
+            None => Ok(()),
+        }
+    }
 
-        res
+    /// Sets `source` to `value` serialized to JSON, e.g. a struct deriving [`Serialize`].
+    /// If serialization fails, [`DiffBuilder::build`] returns [`DiffBuilderError::Serialization`].
+    pub fn source_serializable<T: Serialize + ?Sized>(&mut self, value: &T) -> &mut Self {
+        match serde_json::to_value(value) {
+            Ok(value) => self.source = Some(value),
+            Err(err) => self.errors.push(DiffBuilderError::Serialization { field: "source", error: err.to_string() }),
+        }
+        self
     }
 
-    #[must_use]
-    fn objects(
-        &mut self,
-        source: serde_json::Map<String, serde_json::Value>,
-        mut target: serde_json::Map<String, serde_json::Value>,
-    ) -> Option<Map<String, EntryDifference>> {
-        let mut is_first = true;
-        let mut value_differences = source
-            .into_iter()
-            .filter_map(|(key, source)| {
-                let elem_path = PathElement::Key(key.clone());
-                match is_first {
-                    true => is_first = false,
-                    false => { self.curr_path.pop(); }
-                }
-                self.curr_path.push(elem_path);
+    /// Sets `target` to `value` serialized to JSON, see [`DiffBuilder::source_serializable`].
+    pub fn target_serializable<T: Serialize + ?Sized>(&mut self, value: &T) -> &mut Self {
+        match serde_json::to_value(value) {
+            Ok(value) => self.target = Some(value),
+            Err(err) => self.errors.push(DiffBuilderError::Serialization { field: "target", error: err.to_string() }),
+        }
+        self
+    }
 
-                if self.ignore_path(target.contains_key(&key)) {
-                    target.remove(&key);
-                    return None;
-                }
+    /// Sets `source` to `json5` parsed as [JSON5](https://json5.org), a superset of JSON
+    /// that allows comments, trailing commas, unquoted keys, etc., so JSONC config files can be compared as is.
+    /// If parsing fails, [`DiffBuilder::build`] returns [`DiffBuilderError::Parse`].
+    #[cfg(feature = "json5")]
+    pub fn source_json5(&mut self, json5: &str) -> &mut Self {
+        match json5::from_str(json5) {
+            Ok(value) => self.source = Some(value),
+            Err(err) => self.errors.push(DiffBuilderError::Parse { field: "source", error: err.to_string() }),
+        }
+        self
+    }
 
-                let Some(target) = target.remove(&key) else {
-                    return Some((key, EntryDifference::Extra {
-                        value: source
-                    }));
-                };
+    /// Sets `target` to `json5` parsed as JSON5, see [`DiffBuilder::source_json5`].
+    #[cfg(feature = "json5")]
+    pub fn target_json5(&mut self, json5: &str) -> &mut Self {
+        match json5::from_str(json5) {
+            Ok(value) => self.target = Some(value),
+            Err(err) => self.errors.push(DiffBuilderError::Parse { field: "target", error: err.to_string() }),
+        }
+        self
+    }
 
-                self.values(source, target).map(|diff| (key, EntryDifference::Value { value_diff: diff }))
-            })
-            .collect::<Vec<_>>();
+    /// Sets both [`DiffBuilder::equate_empty_arrays`] and [`DiffBuilder::equate_empty_objects`],
+    /// so empty arrays and objects are equal to `null`.
+    pub fn equate_empty_collections(&mut self, value: bool) -> &mut Self {
+        self.equate_empty_arrays(value).equate_empty_objects(value)
+    }
 
-        if !is_first { self.curr_path.pop(); }
+    /// Restrict the comparison to the value at `path` and its descendants, e.g. `users.[_].name`.
+    /// Can be called multiple times to compare several subtrees, all other entries are treated as equal.
+    /// If a path is both included and ignored with [`DiffBuilder::ignore_path`], it's ignored.
+    pub fn only_path(&mut self, path: &str) -> &mut Self {
+        if let Some(path) = self.parse_path(path) {
+            self.only_paths.get_or_insert_with(Vec::new).push(path);
+        }
+        self
+    }
 
-        value_differences.extend(target.into_iter().filter_map(|(missing_key, missing_value)| {
-            let elem_path = PathElement::Key(missing_key.clone());
-            self.curr_path.push(elem_path);
-            let ignore = self.ignore_path(false);
+    /// Does the same as [`DiffBuilder::only_path`] for every given path.
+    pub fn only_paths<I, S>(&mut self, paths: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        for path in paths {
+            self.only_path(path.as_ref());
+        }
+        self
+    }
 
-            let res = match ignore {
-                true => None,
-                false => Some((missing_key, EntryDifference::Missing {
-                    value: missing_value,
-                })),
-            };
+    /// Suppress every difference at `path` or under it, e.g. `ignore_subtree("metadata")`, including nested
+    /// keys, array elements and the entry itself when only one of the values has it. Unlike [`DiffBuilder::ignore_path`]
+    /// it also applies to array elements, e.g. `items.[0]`, and the root, and doesn't need `ignore_missing`.
+    /// Array elements that only one of the arrays has are a part of the [`ArrayDifference`] of the array,
+    /// so they are still reported.
+    pub fn ignore_subtree(&mut self, path: &str) -> &mut Self {
+        if let Some(path) = self.parse_path(path) {
+            self.ignored_subtrees.get_or_insert_with(Vec::new).push(path);
+        }
+        self
+    }
 
-            self.curr_path.pop();
-            res
-        }));
+    /// Match the elements of the array at `path` by the value of their `key_field`
+    /// instead of their index, e.g. `array_key("users", "id")`. Matched elements are
+    /// compared recursively, the rest are reported as missing or extra in [`ArrayDifference::Keyed`].
+    /// Elements that are not objects or don't have the key field are never matched.
+    pub fn array_key(&mut self, path: &str, key_field: &str) -> &mut Self {
+        if let Some(path) = self.parse_path(path) {
+            self.array_keys.get_or_insert_with(Vec::new).push((path, key_field.to_string()));
+        }
+        self
+    }
 
-        match value_differences.is_empty() {
-            true => None,
-            false => Some(Map(value_differences))
+    /// Ignore only the differences of `kind` at `path`, e.g. `ignore_path_kind("id", DifferenceKind::Type)`
+    /// for a field that legitimately switches between a string and a number, while its value changes are still reported.
+    /// [`DifferenceKind::Missing`] and [`DifferenceKind::Extra`] match object entries at `path`,
+    /// array elements that only one of the arrays has are a part of the [`DifferenceKind::Array`] of the array.
+    /// Objects or arrays of an ignored [`DifferenceKind::Object`] or [`DifferenceKind::Array`] aren't compared at all.
+    /// Can be called multiple times to ignore several kinds.
+    pub fn ignore_path_kind(&mut self, path: &str, kind: DifferenceKind) -> &mut Self {
+        if let Some(path) = self.parse_path(path) {
+            self.ignored_kinds.get_or_insert_with(Vec::new).push((path, kind));
         }
+        self
     }
 
-    pub fn compare(mut self) -> Option<Difference> {
-        self.values(self.source.clone(), self.target.clone())
+    /// Compare the array at `path` as a multiset, so the order of its elements doesn't matter,
+    /// e.g. `unordered_array("users.[_].roles")`. Every element of `source` is matched with an equal
+    /// element of `target`, the rest of them are reported as missing or extra in [`ArrayDifference::Keyed`]
+    /// without any pairs. Arrays matched by [`DiffBuilder::array_key`] are compared by their keys instead.
+    /// It's as expensive as [`ArrayAlignment::Lcs`], as every element of `source` is compared with every element of `target`.
+    pub fn unordered_array(&mut self, path: &str) -> &mut Self {
+        if let Some(path) = self.parse_path(path) {
+            self.unordered_arrays.get_or_insert_with(Vec::new).push(path);
+        }
+        self
     }
 
-    fn values(&mut self, source: serde_json::Value, target: serde_json::Value) -> Option<Difference> {
-        use serde_json::Value::{Array, Bool, Null, Number, Object, String};
+    /// Ignore the values of `value_type` wherever they appear, e.g. `ignore_value_type(Type::Bool)` for feature flags.
+    /// A difference isn't reported if any of the compared values has the type, including the object entries
+    /// that only one of the objects has. Array elements that only one of the arrays has are still reported.
+    pub fn ignore_value_type(&mut self, value_type: Type) -> &mut Self {
+        self.ignored_types.get_or_insert_with(Vec::new).push(value_type);
+        self
+    }
 
-        match (source, target) {
-            (Null, Null) => None,
-            (Bool(source), Bool(target)) => {
-                if source == target {
-                    None
-                } else {
-                    Some(Difference::Scalar(ScalarDifference::Bool {
-                        source,
-                        target,
-                    }))
-                }
-            }
-            (Number(source), Number(target)) => {
-                self.compare_numbers(source, target)
-            }
-            (String(source), String(target)) => {
-                self.compare_strings(source, target)
-            }
-            (Array(source), Array(target)) => self.arrays(source, target).map(Difference::Array),
-            (Object(source), Object(target)) => {
-                self.objects(source, target)
-                    .map(|different_entries| Difference::Object { different_entries })
-            }
-            (Array(source), Null) if self.equate_empty_arrays && source.len().eq(&0) => None,
-            (Null, Array(target)) if self.equate_empty_arrays && target.len().eq(&0) => None,
-            (source, target) => {
-                Some(Difference::Type {
-                    source_type: source.clone().into(),
-                    source_value: source,
-                    target_type: target.clone().into(),
-                    target_value: target,
-                })
-            }
+    /// Compare the values at `path` with `compare` instead of the default comparison,
+    /// e.g. to compare semantic versions or JSON encoded as a string. The values are equal if it returns true,
+    /// otherwise they are reported as a [`ScalarDifference`] if they are scalars of the same type,
+    /// or as a [`Difference::Type`]. If several paths match, the first added one is used.
+    pub fn custom_comparator<F>(&mut self, path: &str, compare: F) -> &mut Self
+    where
+        F: Fn(&serde_json::Value, &serde_json::Value) -> bool + Send + Sync + 'static,
+    {
+        if let Some(path) = self.parse_path(path) {
+            let comparator = CustomComparator { path, compare: Arc::new(compare) };
+            self.custom_comparators.get_or_insert_with(Vec::new).push(comparator);
         }
+        self
     }
 
+    /// Use `epsilon` instead of [`DiffBuilder::approx_float_eq_epsilon`] for the numbers at `path`,
+    /// e.g. `approx_float_eq_epsilon_for_path("prices.[_].amount", 0.0)` to compare prices exactly.
+    /// If several paths match, the first added one is used.
+    #[cfg(feature = "float-approx")]
+    pub fn approx_float_eq_epsilon_for_path(&mut self, path: &str, epsilon: f64) -> &mut Self {
+        if let Some(path) = self.parse_path(path) {
+            self.approx_float_eq_epsilons.get_or_insert_with(Vec::new).push((path, epsilon));
+        }
+        self
+    }
 
-    fn compare_strings(&self, source:String, target: String) -> Option<Difference> {
-        if !self.approx_date_time_eq_duration.is_zero() {
-            let source_datetime = DateTime::parse_from_rfc3339(source.as_str());
-            let target_datetime = DateTime::parse_from_rfc3339(target.as_str());
-
-            match (source_datetime, target_datetime) {
-                (Ok(source_date_time), Ok(target_date_time)) => {
-                    let delta = source_date_time - target_date_time;
-                    let delta = delta.abs().to_std().unwrap();
-                    if delta.gt(&self.approx_date_time_eq_duration) {
-                        return Some(Difference::Scalar(ScalarDifference::String {
-                            source,
-                            target,
-                        }))
-                    } else {
-                        return None
-                    }
-                },
-                (_, _) => {},
-            }
+    /// Compare only the first `len` characters of the strings at `path`, e.g. for hashes or
+    /// URLs where `target` appends a suffix like a cache-busting query string. Strings are normalized
+    /// before that, see [`DiffBuilder::normalize_whitespace`]. If several paths match, the first added one is used.
+    pub fn string_compare_prefix(&mut self, path: &str, len: usize) -> &mut Self {
+        if let Some(path) = self.parse_path(path) {
+            self.string_compare_prefixes.get_or_insert_with(Vec::new).push((path, len));
         }
-        if source == target {
-            None
-        } else {
-            Some(Difference::Scalar(ScalarDifference::String {
-                source,
-                target,
-            }))
+        self
+    }
+
+    /// Allows the array at `path` to have up to `max_extra` elements appended in `target`,
+    /// e.g. for append-only collections like logs where some new entries are expected.
+    /// The elements both arrays have are still compared, the appended ones aren't reported
+    /// as [`ArrayDifference::Shorter`] unless there are more of them. Removed elements are always reported.
+    /// Only arrays compared by index are affected, see [`DiffBuilder::array_alignment`].
+    /// If several paths match, the first added one is used.
+    pub fn array_extra_tolerance(&mut self, path: &str, max_extra: usize) -> &mut Self {
+        if let Some(path) = self.parse_path(path) {
+            self.array_extra_tolerances.get_or_insert_with(Vec::new).push((path, max_extra));
         }
+        self
     }
+}
 
-    fn compare_numbers(&self, source: serde_json::Number, target: serde_json::Number) -> Option<Difference> {
-        if source.is_u64() && target.is_u64() || source.is_i64() && target.is_i64() {
-            if source == target {
-                None
-            } else {
-                Some(Difference::Scalar(ScalarDifference::Number {
-                    source,
-                    target,
-                }))
+/// Writes `value` as JSON with the entries of objects sorted by their keys.
+fn write_canonical(value: &serde_json::Value, out: &mut String) {
+    match value {
+        serde_json::Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by_key(|(key, _)| *key);
+            out.push('{');
+            for (idx, (key, value)) in entries.into_iter().enumerate() {
+                if idx > 0 {
+                    out.push(',');
+                }
+                out.push_str(&serde_json::Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical(value, out);
             }
-        } else if source.is_f64() || target.is_f64() {
-            if relative_eq!(source.as_f64().unwrap(), target.as_f64().unwrap(), epsilon = self.approx_float_eq_epsilon) {
-                None
-            } else {
-                Some(Difference::Scalar(ScalarDifference::Number {
-                    source,
-                    target,
-                }))
+            out.push('}');
+        }
+        serde_json::Value::Array(elements) => {
+            out.push('[');
+            for (idx, element) in elements.iter().enumerate() {
+                if idx > 0 {
+                    out.push(',');
+                }
+                write_canonical(element, out);
             }
-        } else {
-            None
+            out.push(']');
         }
+        scalar => out.push_str(&scalar.to_string()),
     }
+}
 
-    /// Returns true if the current path should be ignored.
-    /// `has_key` indicates if the opposite object has the key.
-    /// So, if the function is called when the keys of source are iterated
-    /// target should be checked for key existence.
-    /// After it can only be called on vector of target keys, which
-    /// means that all those keys are missing on the source.
-    fn ignore_path(&self, has_key: bool) -> bool {
-        let path = self.ignore_paths.iter().find(|p| p.path.eq(&self.curr_path));
-        let path = if let Some(path) = path {path} else {return false;};
-
-        match (path.conditions.len() > 0, path.ignore_missing, has_key) {
-            (true, _, _) => {
-                path.conditions.iter().any(|condition: &IgnorePathCondition| {
-                    match condition {
-                        IgnorePathCondition::Rhai(script) => {
-                            let mut engine = rhai::Engine::new();
-                            engine.register_fn("value_by_path", rhai_script::value_by_path);
-                            let source = engine.parse_json(self.source.to_string(), true).unwrap();
-                            let target = engine.parse_json(self.target.to_string(), true).unwrap();
-                            let mut scope = rhai::Scope::new();
-                            scope.push("source", source);
-                            scope.push("target", target);
-                            scope.push("curr_path", self.curr_path.clone());
-
-                            let result = engine.eval_with_scope::<bool>(&mut scope, script.as_str());
-                            result.unwrap_or(false)
-                        }
-                    }
-                })
-            },
-            (false, false, true) => true,
-            (false, true, false) => true,
-            (false, false, false) => false,
-            (false, true, true) => true,
-        }
+/// Formats a number key of a keyed array, so `1`, `1.0` and `"1e0"` parsed as a number are matched.
+fn number_key(number: &serde_json::Number) -> String {
+    if let Some(int) = number.as_i64() {
+        return int.to_string();
     }
+    if let Some(uint) = number.as_u64() {
+        return uint.to_string();
+    }
+    number.as_f64().map_or_else(|| number.to_string(), |float| float.to_string())
 }
 
-impl From<serde_json::Value> for Type {
-    fn from(value: serde_json::Value) -> Self {
-        match value {
-            serde_json::Value::Null => Type::Null,
-            serde_json::Value::Bool(_) => Type::Bool,
-            serde_json::Value::Number(_) => Type::Number,
-            serde_json::Value::String(_) => Type::String,
-            serde_json::Value::Array(_) => Type::Array,
-            serde_json::Value::Object(_) => Type::Object,
-        }
+/// Appends an equal element to `ops`, extending the last [`ArrayOp::Keep`] if there is one.
+fn push_keep(ops: &mut Vec<ArrayOp>) {
+    match ops.last_mut() {
+        Some(ArrayOp::Keep { count }) => *count += 1,
+        _ => ops.push(ArrayOp::Keep { count: 1 }),
     }
 }
 
-impl PartialEq for ArrayIndex {
-    fn eq(&self, other: &Self) -> bool {
-        match (self, other) {
-            (ArrayIndex::Index(a), ArrayIndex::Index(b)) => a == b,
-            (ArrayIndex::All, ArrayIndex::Index(_)) => true,
-            (ArrayIndex::Index(_), ArrayIndex::All) => true,
-            (ArrayIndex::All, ArrayIndex::All) => true,
-        }
+/// Returns [`ArrayDifference::Ops`] or `None` if all the elements are kept.
+fn array_ops(ops: Vec<ArrayOp>) -> Option<ArrayDifference> {
+    match ops.iter().all(|op| matches!(op, ArrayOp::Keep { .. })) {
+        true => None,
+        false => Some(ArrayDifference::Ops { ops }),
     }
 }
 
-#[derive(Eq, Clone, Debug)]
-#[derive(PartialOrd, Ord)]
-pub enum ArrayIndex {
-    Index(usize),
-    All,
+/// Returns `elements` without the trailing `null` elements, see [`Diff::trim_trailing_nulls`].
+fn trim_trailing_nulls(elements: &[serde_json::Value]) -> &[serde_json::Value] {
+    let len = elements.iter().rposition(|element| !element.is_null()).map_or(0, |idx| idx + 1);
+    &elements[..len]
 }
 
-#[derive(Eq, PartialEq, Clone, Debug, Ord, PartialOrd)]
-pub enum PathElement {
-    Key(String),
-    ArrayIndex(ArrayIndex),
+/// Returns true if the numbers have the same decimal value, e.g. `1e400` and `10.0e399`,
+/// or `None` if any of them isn't a valid JSON number.
+fn decimal_eq(a: &serde_json::Number, b: &serde_json::Number) -> Option<bool> {
+    Some(normalize_decimal(&a.to_string())? == normalize_decimal(&b.to_string())?)
 }
 
-#[derive(PartialEq, Clone, Debug, Builder)]
-pub struct IgnorePath {
-    path: Path,
+/// Splits a JSON number into its sign, significant digits and exponent,
+/// so `-1.50e2` becomes `(true, "15", 1)`. Zero is always `(false, "", 0)`.
+fn normalize_decimal(number: &str) -> Option<(bool, String, i64)> {
+    let (negative, number) = match number.strip_prefix('-') {
+        Some(number) => (true, number),
+        None => (false, number),
+    };
+    let (mantissa, exponent) = match number.find(['e', 'E']) {
+        Some(idx) => (&number[..idx], number[idx + 1..].parse::<i64>().ok()?),
+        None => (number, 0),
+    };
+    let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    if integer.is_empty() || !integer.chars().chain(fraction.chars()).all(|c| c.is_ascii_digit()) {
+        return None;
+    }
 
-    #[builder(default = false)]
-    ignore_missing: bool,
+    let digits = format!("{}{}", integer, fraction);
+    let digits = digits.trim_start_matches('0');
+    let significant = digits.trim_end_matches('0');
+    if significant.is_empty() {
+        return Some((false, String::new(), 0));
+    }
+    let exponent = exponent - fraction.len() as i64 + (digits.len() - significant.len()) as i64;
 
-    #[builder(default = vec![])]
-    conditions: Vec<IgnorePathCondition>
+    Some((negative, significant.to_string(), exponent))
 }
 
-impl IgnorePathBuilder {
-    pub fn condition(&mut self, condition: IgnorePathCondition) -> &mut Self {
-        self.conditions.get_or_insert_with(Vec::new).push(condition);
-        self
+/// Returns the difference of the values as a whole without comparing their elements.
+fn whole_values_difference(source: &serde_json::Value, target: &serde_json::Value) -> Difference {
+    use serde_json::Value::{Bool, Number, String};
+
+    match (source, target) {
+        (Bool(source), Bool(target)) => Difference::Scalar(ScalarDifference::Bool { source: *source, target: *target }),
+        (String(source), String(target)) => {
+            Difference::Scalar(ScalarDifference::String { source: source.clone(), target: target.clone(), edits: None })
+        }
+        (Number(source), Number(target)) => {
+            Difference::Scalar(ScalarDifference::number(source, target))
+        }
+        (source, target) => Difference::Type {
+            source_type: source.into(),
+            source_value: source.clone(),
+            target_type: target.into(),
+            target_value: target.clone(),
+        },
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
-pub enum IgnorePathCondition {
-    Rhai(String)
+/// A pair of containers whose elements are being compared, see [`Diff::values`].
+enum Frame<'a> {
+    Object {
+        entries: serde_json::map::Iter<'a>,
+        source: &'a serde_json::Map<String, serde_json::Value>,
+        target: &'a serde_json::Map<String, serde_json::Value>,
+        different_entries: Vec<(String, EntryDifference)>,
+        /// The key of the entry whose values are being compared
+        key: Option<String>,
+        /// Keys of the compared entries in the order of `source`, see [`Diff::ordered_object_keys`]
+        compared_keys: Vec<&'a String>,
+    },
+    Array {
+        source: &'a [serde_json::Value],
+        target: &'a [serde_json::Value],
+        different_pairs: Vec<(usize, Difference)>,
+        /// The index of the next pair of elements to compare
+        next: usize,
+    },
 }
 
-#[derive(PartialEq, Clone, Debug, Default)]
-pub struct Path(Vec<PathElement>);
+/// Receives the differences found by [`Diff::values_with`] with their path.
+type OnDifference<'a> = dyn FnMut(&Path, Difference) + 'a;
 
-impl Deref for Path {
-    type Target = Vec<PathElement>;
+enum Step {
+    /// Values were compared right away
+    Done(Option<Difference>),
+    /// Containers were pushed to the stack to compare their elements
+    Pushed,
+}
 
-    fn deref(&self) -> &Self::Target {
-        &self.0
+impl Diff {
+    fn arrays<'a>(
+        &self,
+        curr_path: &mut Path,
+        source: &'a [serde_json::Value],
+        target: &'a [serde_json::Value],
+        stack: &mut Vec<Frame<'a>>,
+    ) -> Result<Step, CompareError> {
+        let (source, target) = match self.trim_trailing_nulls {
+            true => (trim_trailing_nulls(source), trim_trailing_nulls(target)),
+            false => (source, target),
+        };
+        let key_field = self.array_keys.iter()
+            .find(|(path, _)| path.matches(curr_path))
+            .map(|(_, key_field)| key_field.as_str());
+        if let Some(key_field) = key_field {
+            return Ok(Step::Done(self.keyed_arrays(curr_path, source, target, key_field)?.map(Difference::Array)));
+        }
+        if self.unordered_arrays.iter().any(|path| path.matches(curr_path)) {
+            return Ok(Step::Done(self.multiset_arrays(curr_path, source, target)?.map(Difference::Array)));
+        }
+        if self.array_alignment == ArrayAlignment::Lcs {
+            return Ok(Step::Done(self.aligned_arrays(curr_path, source, target)?.map(Difference::Array)));
+        }
+        let max_extra = self.array_extra_tolerances.iter().find(|(path, _)| path.matches(curr_path));
+        let target = match max_extra {
+            Some((_, max_extra)) if target.len() > source.len() && target.len() - source.len() <= *max_extra => {
+                &target[..source.len()]
+            }
+            _ => target,
+        };
+
+        #[cfg(feature = "rayon")]
+        if self.parallel && !self.first_difference_only {
+            let different_pairs = self.parallel_pairs(curr_path, source, target)?;
+            let next = source.len().min(target.len());
+            return Ok(Step::Done(self.finish_frame(curr_path, Frame::Array { source, target, different_pairs, next })));
+        }
+
+        stack.push(Frame::Array { source, target, different_pairs: vec![], next: 0 });
+        Ok(Step::Pushed)
     }
-}
 
-impl FromIterator<PathElement> for Path {
-    fn from_iter<T: IntoIterator<Item=PathElement>>(iter: T) -> Self {
-        let mut path = Path::default();
-        for element in iter {
-            path.push(element);
+    /// Returns the equality of every pair of elements, `source[i]` and `target[j]`
+    /// are equal if `equal[i * target.len() + j]` is true.
+    ///
+    /// The pairs are only tried out, so the equal values they have aren't recorded,
+    /// the callers compare the pairs they match again to record them, see [`Diff::record_unchanged`].
+    fn equal_matrix(
+        &self,
+        curr_path: &mut Path,
+        source: &[serde_json::Value],
+        target: &[serde_json::Value],
+    ) -> Result<Vec<bool>, CompareError> {
+        if !self.record_unchanged {
+            return self.equal_pairs(curr_path, source, target);
         }
 
-        path
+        let (leaves, paths) = {
+            let unchanged = self.unchanged_values.0.lock().unwrap();
+            (unchanged.leaves, unchanged.paths.len())
+        };
+        let equal = self.equal_pairs(curr_path, source, target)?;
+        let mut unchanged = self.unchanged_values.0.lock().unwrap();
+        unchanged.leaves = leaves;
+        unchanged.paths.truncate(paths);
+
+        Ok(equal)
     }
-}
 
-impl Path {
-    fn replace_array_index_all_by_exact_path(&self, exact_path: Path) -> Option<Path> {
-        if exact_path.iter().any(|elem| {
-            match  elem {
-                PathElement::ArrayIndex(ArrayIndex::All) => true,
-                _ => false
+    fn equal_pairs(
+        &self,
+        curr_path: &mut Path,
+        source: &[serde_json::Value],
+        target: &[serde_json::Value],
+    ) -> Result<Vec<bool>, CompareError> {
+        #[cfg(feature = "rayon")]
+        if self.parallel {
+            return self.parallel_equal_matrix(curr_path, source, target);
+        }
+
+        let mut equal = Vec::with_capacity(source.len() * target.len());
+        for (i, source) in source.iter().enumerate() {
+            let mut path = curr_path.push_scoped(PathElement::ArrayIndex(ArrayIndex::Index(i)));
+            for target in target {
+                equal.push(self.values(&mut path, source, target)?.is_none());
             }
-        }) {
-            return None
         }
 
-        let res = self.iter().zip(0..self.len()).map_while(|(elem, idx)| {
-            match elem {
-                PathElement::ArrayIndex(ArrayIndex::All) => {
-                    exact_path.get(idx).cloned()
-                },
-                _ => Some(elem.clone())
+        Ok(equal)
+    }
+
+    fn keyed_arrays(
+        &self,
+        curr_path: &mut Path,
+        source: &[serde_json::Value],
+        target: &[serde_json::Value],
+        key_field: &str,
+    ) -> Result<Option<ArrayDifference>, CompareError> {
+        let mut target_indices: HashMap<String, VecDeque<usize>> = HashMap::new();
+        for (idx, elem) in target.iter().enumerate() {
+            if let Some(key) = elem.get(key_field) {
+                target_indices.entry(self.key_string(key)).or_default().push_back(idx);
             }
-        }).collect::<Path>();
+        }
+
+        let mut matched = vec![false; target.len()];
+        let mut different_pairs = vec![];
+        let mut extra_elements = vec![];
+        for (source_index, source) in source.iter().enumerate() {
+            let key = source.get(key_field);
+            let target_index = key
+                .and_then(|key| target_indices.get_mut(&self.key_string(key)))
+                .and_then(|indices| indices.pop_front());
+            let (Some(key), Some(target_index)) = (key, target_index) else {
+                extra_elements.push((source_index, source.clone()));
+                continue;
+            };
+
+            let mut path = curr_path.push_scoped(PathElement::ArrayIndex(ArrayIndex::Index(source_index)));
+            matched[target_index] = true;
+            if let Some(difference) = self.values(&mut path, source, &target[target_index])? {
+                different_pairs.push(KeyedPair { key: key.clone(), source_index, target_index, difference });
+            }
+        }
+        let missing_elements: Vec<_> = target.iter()
+            .enumerate()
+            .filter(|(idx, _)| !matched[*idx])
+            .map(|(idx, elem)| (idx, elem.clone()))
+            .filter(|_| self.mode != DiffMode::Additive)
+            .collect();
+
+        if different_pairs.is_empty() && missing_elements.is_empty() && extra_elements.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(ArrayDifference::Keyed {
+            different_pairs,
+            missing_elements: Map(missing_elements),
+            extra_elements: Map(extra_elements),
+        }))
+    }
+
+    /// Returns the string keyed array elements are matched by, see [`Diff::canonicalize_objects`].
+    /// Numeric strings are matched with numbers if strings are coerced, see [`Diff::parse_numeric_strings`].
+    fn key_string(&self, key: &serde_json::Value) -> String {
+        if self.coerce_scalars || self.parse_numeric_strings {
+            match key {
+                serde_json::Value::Number(number) => return number_key(number),
+                serde_json::Value::String(string) => {
+                    if let Ok(number) = string.parse::<serde_json::Number>() {
+                        return number_key(&number);
+                    }
+                    match string.parse::<bool>() {
+                        Ok(bool) if self.coerce_scalars => return bool.to_string(),
+                        _ => {}
+                    }
+                }
+                _ => {}
+            }
+        }
+        match self.canonicalize_objects {
+            true => {
+                let mut canonical = String::new();
+                write_canonical(key, &mut canonical);
+                canonical
+            }
+            false => key.to_string(),
+        }
+    }
+
+    /// Matches every element of `source` with the first unmatched equal element of `target`,
+    /// see [`DiffBuilder::unordered_array`].
+    fn multiset_arrays(
+        &self,
+        curr_path: &mut Path,
+        source: &[serde_json::Value],
+        target: &[serde_json::Value],
+    ) -> Result<Option<ArrayDifference>, CompareError> {
+        let equal = self.equal_matrix(curr_path, source, target)?;
+        let mut matched = vec![false; target.len()];
+        let mut extra_elements = vec![];
+        for (i, elem) in source.iter().enumerate() {
+            match (0..target.len()).find(|&j| !matched[j] && equal[i * target.len() + j]) {
+                Some(j) => {
+                    matched[j] = true;
+                    if self.record_unchanged {
+                        let mut path = curr_path.push_scoped(PathElement::ArrayIndex(ArrayIndex::Index(i)));
+                        self.values(&mut path, elem, &target[j])?;
+                    }
+                }
+                None => extra_elements.push((i, elem.clone())),
+            }
+        }
+        let missing_elements: Vec<_> = target.iter()
+            .enumerate()
+            .filter(|(idx, _)| !matched[*idx])
+            .map(|(idx, elem)| (idx, elem.clone()))
+            .filter(|_| self.mode != DiffMode::Additive)
+            .collect();
+
+        if missing_elements.is_empty() && extra_elements.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(ArrayDifference::Keyed {
+            different_pairs: vec![],
+            missing_elements: Map(missing_elements),
+            extra_elements: Map(extra_elements),
+        }))
+    }
+
+    fn aligned_arrays(
+        &self,
+        curr_path: &mut Path,
+        source: &[serde_json::Value],
+        target: &[serde_json::Value],
+    ) -> Result<Option<ArrayDifference>, CompareError> {
+        enum Edit {
+            Keep,
+            Remove(usize),
+            Insert(usize),
+        }
+
+        let (n, m) = (source.len(), target.len());
+        let equal = self.equal_matrix(curr_path, source, target)?;
+
+        // lcs[i * (m + 1) + j] is the length of the LCS of source[i..] and target[j..]
+        let mut lcs = vec![0usize; (n + 1) * (m + 1)];
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                lcs[i * (m + 1) + j] = if equal[i * m + j] {
+                    lcs[(i + 1) * (m + 1) + j + 1] + 1
+                } else {
+                    lcs[(i + 1) * (m + 1) + j].max(lcs[i * (m + 1) + j + 1])
+                };
+            }
+        }
+
+        let mut edits = vec![];
+        let (mut i, mut j) = (0, 0);
+        while i < n || j < m {
+            if i < n && j < m && equal[i * m + j] {
+                if self.record_unchanged {
+                    let mut path = curr_path.push_scoped(PathElement::ArrayIndex(ArrayIndex::Index(i)));
+                    self.values(&mut path, &source[i], &target[j])?;
+                }
+                edits.push(Edit::Keep);
+                i += 1;
+                j += 1;
+            } else if i < n && (j == m || lcs[(i + 1) * (m + 1) + j] >= lcs[i * (m + 1) + j + 1]) {
+                edits.push(Edit::Remove(i));
+                i += 1;
+            } else {
+                edits.push(Edit::Insert(j));
+                j += 1;
+            }
+        }
+        edits.push(Edit::Keep);
+
+        let mut different_pairs = vec![];
+        let mut removed_elements = vec![];
+        let mut inserted_elements = vec![];
+        let mut ops = vec![];
+        let (mut removed, mut inserted) = (vec![], vec![]);
+        let last = edits.len() - 1;
+        for (edit_idx, edit) in edits.into_iter().enumerate() {
+            match edit {
+                Edit::Remove(i) => removed.push(i),
+                Edit::Insert(j) => inserted.push(j),
+                Edit::Keep => {
+                    let modified = removed.len().min(inserted.len());
+                    for (&i, &j) in removed.iter().zip(inserted.iter()) {
+                        let mut path = curr_path.push_scoped(PathElement::ArrayIndex(ArrayIndex::Index(i)));
+                        match self.values(&mut path, &source[i], &target[j])? {
+                            Some(difference) if self.array_output == ArrayOutput::Ops => {
+                                ops.push(ArrayOp::Replace { index: i, difference });
+                            }
+                            Some(difference) => different_pairs.push((i, difference)),
+                            None => push_keep(&mut ops),
+                        }
+                    }
+                    let removed = removed.drain(..).skip(modified).map(|i| (i, source[i].clone()));
+                    let inserted = inserted.drain(..).skip(modified).filter(|_| self.mode != DiffMode::Additive);
+                    let inserted = inserted.map(|j| (j, target[j].clone()));
+                    if self.array_output == ArrayOutput::Ops {
+                        ops.extend(removed.map(|(index, value)| ArrayOp::Delete { index, value }));
+                        ops.extend(inserted.map(|(index, value)| ArrayOp::Insert { index, value }));
+                        // The last edit only flushes the pending ones.
+                        if edit_idx != last {
+                            push_keep(&mut ops);
+                        }
+                    } else {
+                        removed_elements.extend(removed);
+                        inserted_elements.extend(inserted);
+                    }
+                }
+            }
+        }
+        if self.array_output == ArrayOutput::Ops {
+            return Ok(array_ops(ops));
+        }
+
+        let mut moved_elements = vec![];
+        if self.detect_moved_elements {
+            removed_elements.retain(|(i, element)| {
+                let Some(pos) = inserted_elements.iter().position(|(j, _)| equal[i * m + j]) else {
+                    return true;
+                };
+                let (target_index, _) = inserted_elements.remove(pos);
+                moved_elements.push(MovedElement { source_index: *i, target_index, value: element.clone() });
+                false
+            });
+        }
+
+        if different_pairs.is_empty() && removed_elements.is_empty() && inserted_elements.is_empty() && moved_elements.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(ArrayDifference::Aligned {
+            different_pairs: Map(different_pairs),
+            removed_elements: Map(removed_elements),
+            inserted_elements: Map(inserted_elements),
+            moved_elements,
+        }))
+    }
+
+    /// Returns the next pair of values of the containers in `frame` to compare
+    /// with their path pushed to `curr_path`, or `None` if all of them are compared.
+    fn next_pair<'a>(
+        &self,
+        curr_path: &mut Path,
+        frame: &mut Frame<'a>,
+    ) -> Option<(&'a serde_json::Value, &'a serde_json::Value)> {
+        match frame {
+            Frame::Object { entries, target, different_entries, key: pending_key, compared_keys, .. } => {
+                for (key, source) in entries.by_ref() {
+                    curr_path.push(PathElement::Key(key.clone()));
+
+                    if self.ignore_path(curr_path, target.contains_key(key), target, Some(source), target.get(key)) {
+                    } else if let Some(target) = target.get(key) {
+                        if self.ordered_object_keys {
+                            compared_keys.push(key);
+                        }
+                        *pending_key = Some(key.clone());
+                        return Some((source, target));
+                    } else if !(self.equals_missing(source)
+                        || self.is_kind_ignored(curr_path, DifferenceKind::Extra))
+                    {
+                        different_entries.push((key.clone(), EntryDifference::Extra {
+                            value: source.clone()
+                        }));
+                        if self.first_difference_only {
+                            curr_path.pop();
+                            return None;
+                        }
+                    }
+
+                    curr_path.pop();
+                }
+
+                None
+            }
+            Frame::Array { source, target, next, .. } => {
+                let idx = *next;
+                if idx >= source.len().min(target.len()) {
+                    return None;
+                }
+                *next += 1;
+                curr_path.push(PathElement::ArrayIndex(ArrayIndex::Index(idx)));
+                Some((&source[idx], &target[idx]))
+            }
+        }
+    }
+
+    /// Records the difference of the values returned by [`Diff::next_pair`] and pops their path.
+    fn push_difference(&self, curr_path: &mut Path, frame: &mut Frame, difference: Option<Difference>) {
+        match (frame, difference) {
+            (Frame::Object { different_entries, key, .. }, Some(difference)) => {
+                different_entries.push((key.take().unwrap(), EntryDifference::Value { value_diff: difference }));
+            }
+            (Frame::Array { different_pairs, next, .. }, Some(difference)) => {
+                different_pairs.push((*next - 1, difference));
+            }
+            (_, None) => {}
+        }
+        curr_path.pop();
+    }
+
+    /// Returns the difference of the containers in `frame` once all of their pairs are compared.
+    fn finish_frame(&self, curr_path: &mut Path, frame: Frame) -> Option<Difference> {
+        match frame {
+            Frame::Object { source, target, mut different_entries, compared_keys, .. } => {
+                let missing_entries = target.iter().filter(|(key, _)| !source.contains_key(*key));
+                different_entries.extend(missing_entries.filter_map(|(missing_key, missing_value)| {
+                    let path = curr_path.push_scoped(PathElement::Key(missing_key.clone()));
+                    let ignore = self.mode == DiffMode::Additive
+                        || self.equals_missing(missing_value)
+                        || self.ignore_path(&path, false, target, None, Some(missing_value))
+                        || self.is_kind_ignored(&path, DifferenceKind::Missing);
+
+                    match ignore {
+                        true => None,
+                        false => Some((missing_key.clone(), EntryDifference::Missing {
+                            value: missing_value.clone(),
+                        })),
+                    }
+                }));
+                if self.sort_output {
+                    different_entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+                }
+                let mut truncated_count = 0;
+                if self.max_entry_differences != 0 && different_entries.len() > self.max_entry_differences {
+                    truncated_count = different_entries.len() - self.max_entry_differences;
+                    different_entries.truncate(self.max_entry_differences);
+                }
+
+                let key_order = self.key_order(&compared_keys, target);
+                match different_entries.is_empty() && key_order.is_none() {
+                    true => None,
+                    false => Some(Difference::Object { different_entries: Map(different_entries), truncated_count, key_order }),
+                }
+            }
+            Frame::Array { source, target, different_pairs, .. } if self.array_output == ArrayOutput::Ops => {
+                let mut differences = different_pairs.into_iter().peekable();
+                let mut ops = vec![];
+                for idx in 0..source.len().min(target.len()) {
+                    match differences.next_if(|(pair_idx, _)| *pair_idx == idx) {
+                        Some((index, difference)) => ops.push(ArrayOp::Replace { index, difference }),
+                        None => push_keep(&mut ops),
+                    }
+                }
+                for (index, value) in source.iter().enumerate().skip(target.len()) {
+                    ops.push(ArrayOp::Delete { index, value: value.clone() });
+                }
+                if self.mode != DiffMode::Additive {
+                    for (index, value) in target.iter().enumerate().skip(source.len()) {
+                        ops.push(ArrayOp::Insert { index, value: value.clone() });
+                    }
+                }
+                array_ops(ops).map(Difference::Array)
+            }
+            Frame::Array { source, target, different_pairs, .. } => {
+                let different_pairs = if different_pairs.is_empty() {
+                    None
+                } else {
+                    Some(Map(different_pairs))
+                };
+
+                let reported = |elements: &[serde_json::Value]| match self.max_reported_array_elements {
+                    0 => elements.len(),
+                    max => elements.len().min(max),
+                };
+                let array_difference = match (source.len(), target.len()) {
+                    (s, t) if s > t => Some(ArrayDifference::Longer {
+                        different_pairs,
+                        target_length: t,
+                        extra_length: s - t,
+                        extra_elements: source[t..t + reported(&source[t..])].to_vec(),
+                        truncated_count: s - t - reported(&source[t..]),
+                    }),
+                    (s, t) if s < t && self.mode != DiffMode::Additive => Some(ArrayDifference::Shorter {
+                        different_pairs,
+                        source_length: s,
+                        missing_elements: target[s..s + reported(&target[s..])].to_vec(),
+                        truncated_count: t - s - reported(&target[s..]),
+                    }),
+                    _ => different_pairs.map(|pairs| ArrayDifference::PairsOnly { different_pairs: pairs }),
+                };
+                array_difference.map(Difference::Array)
+            }
+        }
+    }
+
+    /// Compares `source` with `target` and returns their difference
+    /// or `None` if they are equal. They can be any JSON values, not only objects,
+    /// differences of the values themselves have the empty root path, e.g. in [`Difference::flatten`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the comparison fails, use [`Diff::try_compare`] for untrusted input.
+    pub fn compare(self) -> Option<Difference> {
+        self.compare_ref()
+    }
+
+    /// Does the same as [`Diff::compare`], but borrows the values instead of consuming them,
+    /// so the same [`Diff`] can be compared again. Only the values that end up
+    /// in the [`Difference`] are cloned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the comparison fails, use [`Diff::try_compare_ref`] for untrusted input.
+    pub fn compare_ref(&self) -> Option<Difference> {
+        match self.try_compare_ref() {
+            Ok(diff) => diff,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    /// Returns true if `source` and `target` are equal, taking into account all the configured
+    /// settings. Unlike [`Diff::compare`] it stops at the first difference found
+    /// instead of building the whole [`Difference`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the comparison fails, see [`Diff::compare`].
+    pub fn equals(mut self) -> bool {
+        self.first_difference_only = true;
+        self.compare().is_none()
+    }
+
+    /// Does the same as [`Diff::compare`], but returns a [`CompareError`] instead of panicking
+    /// when the values can't be compared.
+    pub fn try_compare(self) -> Result<Option<Difference>, CompareError> {
+        self.try_compare_ref()
+    }
+
+    /// Does the same as [`Diff::compare_ref`], but returns a [`CompareError`] instead of panicking
+    /// when the values can't be compared.
+    pub fn try_compare_ref(&self) -> Result<Option<Difference>, CompareError> {
+        self.values(&mut Path::default(), &self.source, &self.target)
+    }
+
+    /// Returns the ignore paths that didn't match any of the values compared so far, which usually
+    /// means a typo or that the structure of the values has changed. A path counts as used once
+    /// it matches, even if its conditions aren't met.
+    ///
+    /// [`Diff::compare`] consumes the [`Diff`], so use [`Diff::compare_ref`] to check them afterwards.
+    pub fn unused_ignore_paths(&self) -> Vec<&IgnorePath> {
+        let used = self.used_ignore_paths.0.lock().unwrap();
+        self.ignore_paths.iter()
+            .enumerate()
+            .filter(|(idx, _)| !used.contains(idx))
+            .map(|(_, path)| path)
+            .collect()
+    }
+
+    /// Compares `source` with `target` at `curr_path`.
+    fn values(
+        &self,
+        curr_path: &mut Path,
+        source: &serde_json::Value,
+        target: &serde_json::Value,
+    ) -> Result<Option<Difference>, CompareError> {
+        self.values_with(curr_path, source, target, None)
+    }
+
+    /// Compares `source` with `target` at `curr_path`. Nested objects and arrays are compared using an explicit
+    /// stack of [`Frame`]s instead of recursion, so deeply nested values don't overflow the thread stack.
+    ///
+    /// If `on_difference` is set, differences are passed to it with their path as soon as they are found
+    /// instead of being collected into the containers, see [`Diff::compare_with`].
+    fn values_with(
+        &self,
+        curr_path: &mut Path,
+        source: &serde_json::Value,
+        target: &serde_json::Value,
+        mut on_difference: Option<&mut OnDifference>,
+    ) -> Result<Option<Difference>, CompareError> {
+        // Pops whatever the walk below pushed onto `curr_path`, also when it returns early.
+        let mut scope = curr_path.scope();
+        let curr_path: &mut Path = &mut scope;
+        let mut stack = vec![];
+        let mut step = self.begin_values(curr_path, source, target, &mut stack)?;
+        loop {
+            if let Step::Done(difference) = step {
+                let mut difference = difference.filter(|difference| !self.is_kind_ignored(curr_path, difference.kind()));
+                if let Some(on_difference) = on_difference.as_mut() {
+                    if let Some(difference) = difference.take() {
+                        on_difference(curr_path, difference);
+                    }
+                }
+                let Some(frame) = stack.last_mut() else {
+                    return Ok(difference);
+                };
+                if self.first_difference_only && difference.is_some() {
+                    return Ok(difference);
+                }
+                self.push_difference(curr_path, frame, difference);
+            }
+
+            let frame = stack.last_mut().unwrap();
+            step = match self.next_pair(curr_path, frame) {
+                Some((source, target)) => self.begin_values(curr_path, source, target, &mut stack)?,
+                None => {
+                    let frame = stack.pop().unwrap();
+                    Step::Done(self.finish_frame(curr_path, frame))
+                }
+            };
+        }
+    }
+
+    /// Compares scalar values right away, objects and arrays are pushed to `stack`
+    /// to compare their elements one by one.
+    fn begin_values<'a>(
+        &self,
+        curr_path: &mut Path,
+        source: &'a serde_json::Value,
+        target: &'a serde_json::Value,
+        stack: &mut Vec<Frame<'a>>,
+    ) -> Result<Step, CompareError> {
+        use serde_json::Value::{Array, Bool, Null, Number, Object, String};
+
+        if !self.is_included(curr_path) || self.is_in_ignored_subtree(curr_path) || self.has_ignored_type(Some(source), Some(target)) {
+            return Ok(Step::Done(None));
+        }
+        if self.max_depth != 0 && curr_path.len() > self.max_depth {
+            return Err(CompareError::MaxDepthExceeded { path: curr_path.clone(), max_depth: self.max_depth });
+        }
+        // Scalars on the way to an include-only path can't contain it, e.g. `id` for `**.name`.
+        let is_container = |value: &serde_json::Value| value.is_array() || value.is_object();
+        if !is_container(source) && !is_container(target) && !self.is_inside_only_paths(curr_path) {
+            return Ok(Step::Done(None));
+        }
+        let comparator = self.custom_comparators.iter().find(|comparator| comparator.path.matches(curr_path));
+        if let Some(comparator) = comparator {
+            return Ok(Step::Done(match (comparator.compare)(source, target) {
+                true => None,
+                false => Some(whole_values_difference(source, target)),
+            }));
+        }
+        // Containers of an ignored kind are skipped before their elements are compared, so the differences
+        // of the elements aren't passed to `on_difference` or returned early for `first_difference_only` either.
+        let ignored_kind = match (source, target) {
+            (Array(_), Array(_)) => Some(DifferenceKind::Array),
+            (Object(_), Object(_)) => Some(DifferenceKind::Object),
+            _ => None,
+        };
+        if ignored_kind.is_some_and(|kind| self.is_kind_ignored(curr_path, kind)) {
+            return Ok(Step::Done(None));
+        }
+
+        let difference = match (source, target) {
+            (Null, Null) => None,
+            (Bool(source), Bool(target)) => {
+                if source == target {
+                    None
+                } else {
+                    Some(Difference::Scalar(ScalarDifference::Bool {
+                        source: *source,
+                        target: *target,
+                    }))
+                }
+            }
+            (Number(source), Number(target)) => {
+                self.compare_numbers(curr_path, source, target)?
+            }
+            (String(source), String(target)) => {
+                self.compare_strings(curr_path, source, target)?
+            }
+            (Array(source), Array(target)) => return self.arrays(curr_path, source, target, stack),
+            (Object(source_map), Object(target_map)) => {
+                stack.push(Frame::Object {
+                    entries: source_map.iter(),
+                    source: source_map,
+                    target: target_map,
+                    different_entries: vec![],
+                    key: None,
+                    compared_keys: vec![],
+                });
+                return Ok(Step::Pushed);
+            }
+            (Array(source), Null) if self.equate_empty_arrays && source.len().eq(&0) => None,
+            (Null, Array(target)) if self.equate_empty_arrays && target.len().eq(&0) => None,
+            (Object(source), Null) if self.equate_empty_objects && source.is_empty() => None,
+            (Null, Object(target)) if self.equate_empty_objects && target.is_empty() => None,
+            (source, target) if (self.coerce_scalars || self.parse_numeric_strings)
+                && self.coerced_eq(curr_path, source, target)? => None,
+            (source, target) => {
+                Some(Difference::Type {
+                    source_type: source.into(),
+                    source_value: self.type_diff_value(source),
+                    target_type: target.into(),
+                    target_value: self.type_diff_value(target),
+                })
+            }
+        };
+        if self.record_unchanged && difference.is_none() && !is_container(source) && !is_container(target) {
+            let mut unchanged = self.unchanged_values.0.lock().unwrap();
+            unchanged.leaves += 1;
+            if self.include_unchanged {
+                unchanged.paths.push(curr_path.clone());
+            }
+        }
+
+        Ok(Step::Done(difference))
+    }
+
+
+    fn compare_strings(&self, curr_path: &Path, source: &str, target: &str) -> Result<Option<Difference>, CompareError> {
+        let difference = || Some(Difference::Scalar(ScalarDifference::String {
+            source: source.to_string(),
+            target: target.to_string(),
+            edits: self.string_subdiff.then(|| string_edits(source, target)),
+        }));
+
+        #[cfg(feature = "datetime")]
+        if let Some(equal) = self.dates_eq(curr_path, source, target)? {
+            return Ok(if equal { None } else { difference() });
+        }
+        let (source_normalized, target_normalized) = (self.normalize_string(source), self.normalize_string(target));
+        let prefix = self.string_compare_prefixes.iter().find(|(path, _)| path.matches(curr_path));
+        let equal = match prefix {
+            Some((_, len)) => source_normalized.chars().take(*len).eq(target_normalized.chars().take(*len)),
+            None => source_normalized == target_normalized,
+        };
+        if equal {
+            Ok(None)
+        } else {
+            Ok(difference())
+        }
+    }
+
+
+    /// Normalizes a string before the equality check according to the string comparison settings.
+    fn normalize_string<'a>(&self, s: &'a str) -> Cow<'a, str> {
+        let s = match self.normalize_whitespace {
+            WhitespaceMode::Preserve => Cow::Borrowed(s),
+            WhitespaceMode::Trim => Cow::Borrowed(s.trim()),
+            WhitespaceMode::Collapse => Cow::Owned(s.split_whitespace().collect::<Vec<_>>().join(" ")),
+        };
+        if self.case_insensitive_strings {
+            Cow::Owned(s.to_lowercase())
+        } else {
+            s
+        }
+    }
+
+    fn compare_numbers(
+        &self,
+        curr_path: &Path,
+        source: &serde_json::Number,
+        target: &serde_json::Number,
+    ) -> Result<Option<Difference>, CompareError> {
+        if self.min_numeric_change > 0.0 {
+            // Integers are subtracted exactly, `f64` can't tell apart the ones above 2^53, e.g. large ids.
+            let integer = |number: &serde_json::Number| {
+                number.as_i64().map(i128::from).or(number.as_u64().map(i128::from))
+            };
+            let change = match (integer(source), integer(target)) {
+                (Some(source), Some(target)) => Some(source.abs_diff(target) as f64),
+                _ => source.as_f64().zip(target.as_f64()).map(|(source, target)| (source - target).abs()),
+            };
+            if change.is_some_and(|change| change < self.min_numeric_change) {
+                return Ok(None);
+            }
+        }
+
+        // Only pairs of integers are compared exactly, a float on either side makes both of them
+        // compared as `f64` with the tolerances, so `[1, 2.0]` and `[1.0, 2]` are treated the same.
+        let integers = !source.is_f64() && !target.is_f64();
+        if integers && !self.numbers_equal_across_types {
+            // Arbitrary precision integers are compared as strings, e.g. `-0` and `0`.
+            let decimal_eq = || cfg!(feature = "arbitrary_precision") && decimal_eq(source, target) == Some(true);
+            if source == target || decimal_eq() {
+                Ok(None)
+            } else {
+                Ok(Some(Difference::Scalar(ScalarDifference::number(source, target))))
+            }
+        } else {
+            // Arbitrary precision numbers out of `f64` range are compared exactly by their decimal value.
+            let (Some(source_f64), Some(target_f64)) = (source.as_f64(), target.as_f64()) else {
+                return match decimal_eq(source, target) {
+                    Some(true) => Ok(None),
+                    Some(false) => Ok(Some(Difference::Scalar(ScalarDifference::number(source, target)))),
+                    None => {
+                        let number = if source.as_f64().is_none() { source } else { target }.clone();
+                        Err(CompareError::NumberNotRepresentable { path: curr_path.clone(), number })
+                    }
+                };
+            };
+            #[cfg(feature = "float-approx")]
+            let epsilon = self.approx_float_eq_epsilons.iter()
+                .find(|(path, _)| path.matches(curr_path))
+                .map_or(self.approx_float_eq_epsilon, |(_, epsilon)| *epsilon);
+            #[cfg(not(feature = "float-approx"))]
+            let epsilon = 0.0;
+            if self.floats_eq(source_f64, target_f64, epsilon) {
+                Ok(None)
+            } else {
+                Ok(Some(Difference::Scalar(ScalarDifference::number(source, target))))
+            }
+        }
+    }
+
+    /// Returns true if a string is equal to a number or a bool after it's parsed, e.g. `"5"` and `5`,
+    /// see [`Diff::coerce_scalars`] and [`Diff::parse_numeric_strings`], which only parses numbers.
+    fn coerced_eq(
+        &self,
+        curr_path: &Path,
+        source: &serde_json::Value,
+        target: &serde_json::Value,
+    ) -> Result<bool, CompareError> {
+        use serde_json::Value::{Bool, Number, String};
+
+        Ok(match (source, target) {
+            (String(string), Number(number)) | (Number(number), String(string)) => {
+                match string.parse::<serde_json::Number>() {
+                    Ok(parsed) => self.compare_numbers(curr_path, &parsed, number)?.is_none(),
+                    Err(_) => false,
+                }
+            }
+            (String(string), Bool(bool)) | (Bool(bool), String(string)) if self.coerce_scalars => {
+                string.parse::<bool>().is_ok_and(|parsed| parsed == *bool)
+            }
+            _ => false,
+        })
+    }
+
+    /// Returns the order of `compared_keys` in `source` and `target` if it's different, see [`Diff::ordered_object_keys`].
+    fn key_order(&self, compared_keys: &[&String], target: &serde_json::Map<String, serde_json::Value>) -> Option<KeyOrder> {
+        if !self.ordered_object_keys {
+            return None;
+        }
+        let compared: HashSet<&String> = compared_keys.iter().copied().collect();
+        let target_keys: Vec<&String> = target.keys().filter(|key| compared.contains(key)).collect();
+        if target_keys == compared_keys {
+            return None;
+        }
+
+        Some(KeyOrder {
+            source: compared_keys.iter().map(|key| key.to_string()).collect(),
+            target: target_keys.into_iter().cloned().collect(),
+        })
+    }
+
+    /// Returns true if an entry with `value` is equal to an absent one, see [`Diff::equate_null_and_missing`].
+    fn equals_missing(&self, value: &serde_json::Value) -> bool {
+        self.equate_null_and_missing && match value {
+            serde_json::Value::Null => true,
+            serde_json::Value::Array(array) => self.equate_empty_arrays && array.is_empty(),
+            serde_json::Value::Object(object) => self.equate_empty_objects && object.is_empty(),
+            _ => false,
+        }
+    }
+
+    /// Returns the value reported by [`Difference::Type`], see [`Diff::compact_type_diff`].
+    fn type_diff_value(&self, value: &serde_json::Value) -> serde_json::Value {
+        const PREVIEW_LENGTH: usize = 64;
+
+        if !self.compact_type_diff || !(value.is_array() || value.is_object()) {
+            return value.clone();
+        }
+        let json = value.to_string();
+        if json.len() <= PREVIEW_LENGTH {
+            return value.clone();
+        }
+        let end = (0..=PREVIEW_LENGTH).rev().find(|idx| json.is_char_boundary(*idx)).unwrap_or(0);
+        serde_json::Value::String(format!("{}...", &json[..end]))
+    }
+
+    /// Returns true if the floats are equal within `epsilon` or [`Diff::approx_float_abs_epsilon`].
+    /// Infinities are equal only to the infinity of the same sign, and NaN is equal to NaN
+    /// only if [`Diff::nan_equals_nan`] is set.
+    #[cfg_attr(not(feature = "float-approx"), allow(unused_variables))]
+    fn floats_eq(&self, source: f64, target: f64, epsilon: f64) -> bool {
+        if source.is_nan() || target.is_nan() {
+            return self.nan_equals_nan && source.is_nan() && target.is_nan();
+        }
+        if source.is_infinite() || target.is_infinite() {
+            return source == target;
+        }
+        // Numbers are equal if they pass any of the relative, absolute or percentage tolerance checks.
+        #[cfg(feature = "float-approx")]
+        if relative_eq!(source, target, epsilon = epsilon)
+            || abs_diff_eq!(source, target, epsilon = self.approx_float_abs_epsilon)
+            || (source - target).abs() <= self.approx_float_eq_percent / 100.0 * source.abs().max(target.abs())
+        {
+            return true;
+        }
+
+        source == target
+    }
+
+    /// Returns true if differences of `kind` are ignored at the current path, see [`Diff::ignored_kinds`].
+    fn is_kind_ignored(&self, curr_path: &Path, kind: DifferenceKind) -> bool {
+        self.ignored_kinds.iter().any(|(path, ignored)| *ignored == kind && path.matches(curr_path))
+    }
+
+    /// Returns true if any of the values has one of [`Diff::ignored_types`].
+    fn has_ignored_type(&self, source: Option<&serde_json::Value>, target: Option<&serde_json::Value>) -> bool {
+        !self.ignored_types.is_empty()
+            && source.into_iter().chain(target).any(|value| self.ignored_types.contains(&Type::from(value)))
+    }
+
+    /// Returns true if the current path is inside or leads to one of [`Diff::only_paths`],
+    /// or no include-only paths are set.
+    fn is_included(&self, curr_path: &Path) -> bool {
+        self.only_paths.is_empty() || self.only_paths.iter().any(|path| path.matches_partially(curr_path))
+    }
+
+    /// Returns true if the current path is one of [`Diff::ignored_subtrees`] or inside it.
+    fn is_in_ignored_subtree(&self, curr_path: &Path) -> bool {
+        self.ignored_subtrees.iter().any(|path| path.matches_or_contains(curr_path))
+    }
+
+    /// Returns true if the current path is inside one of [`Diff::only_paths`], or no include-only paths are set.
+    fn is_inside_only_paths(&self, curr_path: &Path) -> bool {
+        self.only_paths.is_empty() || self.only_paths.iter().any(|path| path.matches_or_contains(curr_path))
+    }
+
+    /// Returns true if the current path should be ignored.
+    /// `has_key` indicates if the opposite object has the key.
+    /// So, if the function is called when the keys of source are iterated
+    /// target should be checked for key existence.
+    /// After it can only be called on vector of target keys, which
+    /// means that all those keys are missing on the source.
+    /// `source_value` and `target_value` are the values of the key in source and target,
+    /// they are checked by the conditions and [`Diff::ignored_types`].
+    fn ignore_path(
+        &self,
+        curr_path: &Path,
+        has_key: bool,
+        target_object: &serde_json::Map<String, serde_json::Value>,
+        source_value: Option<&serde_json::Value>,
+        target_value: Option<&serde_json::Value>,
+    ) -> bool {
+        if !self.is_included(curr_path) || self.is_in_ignored_subtree(curr_path) {
+            return true;
+        }
+        if self.has_ignored_type(source_value, target_value) {
+            return true;
+        }
+
+        let idx = self.ignore_paths.iter().position(|p| p.path.matches(curr_path));
+        let idx = if let Some(idx) = idx {idx} else {return false;};
+        self.used_ignore_paths.0.lock().unwrap().insert(idx);
+        let path = &self.ignore_paths[idx];
+
+        match (path.conditions.len() > 0, path.ignore_missing, has_key) {
+            (true, _, _) => {
+                path.conditions.iter().any(|condition: &IgnorePathCondition| {
+                    match condition {
+                        #[cfg(feature = "rhai")]
+                        IgnorePathCondition::Rhai(script) => {
+                            let mut engine = rhai::Engine::new();
+                            engine.register_fn("value_by_path", rhai_script::value_by_path);
+                            let source = engine.parse_json(self.source.to_string(), true).unwrap();
+                            let target = engine.parse_json(self.target.to_string(), true).unwrap();
+                            let mut scope = rhai::Scope::new();
+                            scope.push("source", source);
+                            scope.push("target", target);
+                            scope.push("curr_path", curr_path.clone());
+                            scope.push("current_path_string", curr_path.to_string());
+                            scope.push("source_value", rhai_script::to_dynamic(source_value));
+                            scope.push("target_value", rhai_script::to_dynamic(target_value));
+
+                            let result = engine.eval_with_scope::<bool>(&mut scope, script.as_str());
+                            result.unwrap_or(false)
+                        }
+                        IgnorePathCondition::ValueEquals(value) => target_value == Some(value),
+                        IgnorePathCondition::ValueIn(values) => target_value.is_some_and(|target| values.contains(target)),
+                        IgnorePathCondition::SiblingEquals { sibling, value } => target_object.get(sibling) == Some(value),
+                    }
+                })
+            },
+            (false, false, true) => true,
+            (false, true, false) => true,
+            (false, false, false) => false,
+            (false, true, true) => true,
+        }
+    }
+}
+
+impl From<serde_json::Value> for Type {
+    fn from(value: serde_json::Value) -> Self {
+        Type::from(&value)
+    }
+}
+
+impl From<&serde_json::Value> for Type {
+    fn from(value: &serde_json::Value) -> Self {
+        match value {
+            serde_json::Value::Null => Type::Null,
+            serde_json::Value::Bool(_) => Type::Bool,
+            serde_json::Value::Number(_) => Type::Number,
+            serde_json::Value::String(_) => Type::String,
+            serde_json::Value::Array(_) => Type::Array,
+            serde_json::Value::Object(_) => Type::Object,
+        }
+    }
+}
+
+/// An index of an array element in a [`Path`]. [`ArrayIndex::All`] is only a wildcard when a path is matched,
+/// see [`PathElement::matches`], `==` compares the indices as they are, so `[_]` isn't equal to `[0]`.
+#[derive(Eq, PartialEq, Clone, Debug)]
+#[derive(PartialOrd, Ord)]
+pub enum ArrayIndex {
+    Index(usize),
+    All,
+}
+
+#[derive(Eq, PartialEq, Clone, Debug, Ord, PartialOrd)]
+pub enum PathElement {
+    Key(String),
+    ArrayIndex(ArrayIndex),
+    /// Matches any key the pattern matches, written as `~'pattern'` in a string path.
+    KeyPattern(KeyPattern),
+    /// Matches zero or more path elements, written as `**` in a string path.
+    AnyDepth,
+}
+
+impl PathElement {
+    /// Returns a [`PathElement::Key`] element.
+    pub fn key(key: impl Into<String>) -> Self {
+        PathElement::Key(key.into())
+    }
+
+    /// Returns a [`PathElement::ArrayIndex`] element of the given index.
+    pub fn index(index: usize) -> Self {
+        PathElement::ArrayIndex(ArrayIndex::Index(index))
+    }
+
+    /// Returns true if `other` is matched by this element. Unlike `==` keys are
+    /// matched by [`PathElement::KeyPattern`] and indices by [`ArrayIndex::All`] as well,
+    /// but not the other way around, e.g. `[_]` matches `[0]`, while `[0]` doesn't match `[_]`.
+    pub fn matches(&self, other: &PathElement) -> bool {
+        match (self, other) {
+            (PathElement::KeyPattern(pattern), PathElement::Key(key)) => pattern.is_match(key),
+            (PathElement::ArrayIndex(ArrayIndex::All), PathElement::ArrayIndex(_)) => true,
+            (a, b) => a.eq(b),
+        }
+    }
+}
+
+impl From<&str> for PathElement {
+    fn from(key: &str) -> Self {
+        PathElement::key(key)
+    }
+}
+
+impl From<String> for PathElement {
+    fn from(key: String) -> Self {
+        PathElement::Key(key)
+    }
+}
+
+impl From<usize> for PathElement {
+    fn from(index: usize) -> Self {
+        PathElement::index(index)
+    }
+}
+
+impl From<ArrayIndex> for PathElement {
+    fn from(index: ArrayIndex) -> Self {
+        PathElement::ArrayIndex(index)
+    }
+}
+
+/// Builds a [`Path`] of keys and array indices, e.g. `path!["users", 1, "age"]` is `users.[1].age`.
+/// Every element is converted with [`PathElement::from`], so [`ArrayIndex::All`] can be used for `[_]`.
+#[macro_export]
+macro_rules! path {
+    ($($element:expr),* $(,)?) => {
+        $crate::Path::from(vec![$($crate::PathElement::from($element)),*])
+    };
+}
+
+/// A regular expression matching object keys, e.g. `~'_at$'` matches all keys ending with `_at`.
+/// The pattern is not anchored, so `^` and `$` should be used to match whole keys.
+#[derive(Clone, Debug)]
+pub struct KeyPattern(Regex);
+
+impl KeyPattern {
+    pub fn new(pattern: &str) -> Result<Self, regex::Error> {
+        Ok(KeyPattern(Regex::new(pattern)?))
+    }
+
+    pub fn is_match(&self, key: &str) -> bool {
+        self.0.is_match(key)
+    }
+
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl PartialEq for KeyPattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for KeyPattern {}
+
+impl PartialOrd for KeyPattern {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for KeyPattern {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+/// A path ignored during the comparison, see [`DiffBuilder::ignore_path`].
+/// Use [`IgnorePathBuilder`] to build it and [`DiffBuilder::ignore_paths`] to set all of them at once.
+#[derive(PartialEq, Clone, Debug, Builder)]
+pub struct IgnorePath {
+    /// The ignored path, it may contain patterns, e.g. `users.[_].age`.
+    pub path: Path,
+
+    /// If true the path is also ignored when it's missing in the source or target.
+    #[builder(default = false)]
+    pub ignore_missing: bool,
+
+    /// If not empty the path is ignored only if any of the conditions is met,
+    /// [`IgnorePath::ignore_missing`] is not taken into account then.
+    #[builder(default = vec![])]
+    pub conditions: Vec<IgnorePathCondition>
+}
+
+impl IgnorePathBuilder {
+    /// Adds a condition to [`IgnorePath::conditions`].
+    pub fn condition(&mut self, condition: IgnorePathCondition) -> &mut Self {
+        self.conditions.get_or_insert_with(Vec::new).push(condition);
+        self
+    }
+}
+
+/// A condition of [`IgnorePath`], checked every time its path matches the current one.
+/// See [`DiffBuilder::ignore_path_with_condition`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum IgnorePathCondition {
+    /// A rhai script evaluated to a bool, the path is ignored if it returns true.
+    /// Requires the `rhai` feature, which is enabled by default.
+    ///
+    /// The scope of the script contains:
+    /// - `source` and `target` – the whole compared values as rhai maps or arrays;
+    /// - `curr_path` – the [`Path`] of the compared value, it has the real indices of array elements.
+    /// - `current_path_string` – `curr_path` rendered as a string, e.g. `users.[1].age`.
+    /// - `source_value` and `target_value` – the values at `curr_path`, `()` if the value is missing.
+    ///
+    /// `value_by_path(obj, path, curr_path)` returns the value of `obj` at `path`, where `[_]` is replaced
+    /// by the index from `curr_path`, e.g. `target.value_by_path("users.[_].age", curr_path)`, or `()` if there is none.
+    /// See the example `ignore_with_rhai_script.rs`.
+    #[cfg(feature = "rhai")]
+    Rhai(String),
+    /// The path is ignored if its value in `target` is equal to the given one,
+    /// e.g. to ignore `status` when it's `"archived"`.
+    ValueEquals(serde_json::Value),
+    /// The path is ignored if its value in `target` is equal to any of the given ones.
+    ValueIn(Vec<serde_json::Value>),
+    /// The path is ignored if the object containing it has the `sibling` entry equal to `value` in `target`,
+    /// e.g. to ignore `price` when `currency` is `"EUR"`.
+    SiblingEquals { sibling: String, value: serde_json::Value },
+}
+
+type CompareFn = dyn Fn(&serde_json::Value, &serde_json::Value) -> bool + Send + Sync;
+
+/// Indices of the ignore paths that matched, see [`Diff::unused_ignore_paths`]. They are behind a mutex
+/// to be recorded while comparing through a shared reference, clones get a copy of them.
+#[derive(Debug, Default)]
+struct UsedIgnorePaths(Mutex<HashSet<usize>>);
+
+impl Clone for UsedIgnorePaths {
+    fn clone(&self) -> Self {
+        UsedIgnorePaths(Mutex::new(self.0.lock().unwrap().clone()))
+    }
+}
+
+/// The amount of the equal scalar values and their paths if [`Diff::include_unchanged`] is set.
+/// Like [`UsedIgnorePaths`], they are behind a mutex and clones get a copy of them.
+#[derive(Debug, Default)]
+struct UnchangedValues(Mutex<Unchanged>);
+
+#[derive(Debug, Default, Clone)]
+struct Unchanged {
+    leaves: usize,
+    paths: Vec<Path>,
+}
+
+impl Clone for UnchangedValues {
+    fn clone(&self) -> Self {
+        UnchangedValues(Mutex::new(self.0.lock().unwrap().clone()))
+    }
+}
+
+/// A function comparing the values at a path, see [`DiffBuilder::custom_comparator`].
+#[derive(Clone)]
+pub struct CustomComparator {
+    path: Path,
+    compare: Arc<CompareFn>,
+}
+
+impl fmt::Debug for CustomComparator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CustomComparator").field("path", &self.path).finish_non_exhaustive()
+    }
+}
+
+#[derive(PartialEq, Clone, Debug, Default)]
+pub struct Path(Vec<PathElement>);
+
+impl Deref for Path {
+    type Target = Vec<PathElement>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl FromIterator<PathElement> for Path {
+    fn from_iter<T: IntoIterator<Item=PathElement>>(iter: T) -> Self {
+        Path(iter.into_iter().collect())
+    }
+}
+
+impl Extend<PathElement> for Path {
+    fn extend<T: IntoIterator<Item=PathElement>>(&mut self, iter: T) {
+        self.0.extend(iter);
+    }
+}
+
+impl Path {
+    /// Appends a [`PathElement::Key`], returns the path so the calls can be chained,
+    /// e.g. `path.push_key("users").push_index(1)`.
+    pub fn push_key(&mut self, key: impl Into<String>) -> &mut Self {
+        self.push(PathElement::key(key));
+        self
+    }
+
+    /// Appends a [`PathElement::ArrayIndex`] of the given index, see [`Path::push_key`].
+    pub fn push_index(&mut self, index: usize) -> &mut Self {
+        self.push(PathElement::index(index));
+        self
+    }
+
+    /// Returns the value at this path inside `value`, e.g. the changed value of a leaf
+    /// returned by [`Difference::flatten`]. `None` is returned if there is no such value,
+    /// or the path contains wildcards like [`ArrayIndex::All`].
+    pub fn get<'a>(&self, value: &'a serde_json::Value) -> Option<&'a serde_json::Value> {
+        self.iter().try_fold(value, |value, element| match element {
+            PathElement::Key(key) => value.as_object()?.get(key),
+            PathElement::ArrayIndex(ArrayIndex::Index(idx)) => value.as_array()?.get(*idx),
+            PathElement::ArrayIndex(ArrayIndex::All) | PathElement::KeyPattern(_) | PathElement::AnyDepth => None,
+        })
+    }
+
+    /// Returns true if the concrete `path` is matched by this one, taking into account
+    /// wildcards like [`ArrayIndex::All`], [`PathElement::KeyPattern`] and [`PathElement::AnyDepth`].
+    pub fn matches(&self, path: &Path) -> bool {
+        elements_match(self, path, false, false)
+    }
+
+    /// Does the same as [`Path::matches`], but also returns true if `path` is inside the matched one.
+    fn matches_or_contains(&self, path: &Path) -> bool {
+        elements_match(self, path, true, false)
+    }
+
+    /// Does the same as [`Path::matches_or_contains`], but also returns true if `path` leads to the matched one.
+    fn matches_partially(&self, path: &Path) -> bool {
+        elements_match(self, path, true, true)
+    }
+
+    #[cfg_attr(not(feature = "rhai"), allow(dead_code))]
+    fn replace_array_index_all_by_exact_path(&self, exact_path: Path) -> Option<Path> {
+        if exact_path.iter().any(|elem| {
+            match  elem {
+                PathElement::ArrayIndex(ArrayIndex::All) => true,
+                _ => false
+            }
+        }) {
+            return None
+        }
+        if self.contains(&PathElement::AnyDepth) {
+            return None
+        }
+
+        let res = self.iter().zip(0..self.len()).map_while(|(elem, idx)| {
+            match elem {
+                PathElement::ArrayIndex(ArrayIndex::All) => {
+                    exact_path.0.get(idx).cloned()
+                },
+                _ => Some(elem.clone())
+            }
+        }).collect::<Path>();
+
+        if res.len() != self.len() {
+            return None
+        }
+
+        Some(res)
+    }
+
+    /// Returns a guard that restores the path to its current length once dropped,
+    /// no matter which elements were pushed or popped through it in the meantime.
+    fn scope(&mut self) -> ScopedPath<'_> {
+        let len = self.len();
+        ScopedPath { path: self, len }
+    }
+
+    /// Pushes `element` and returns a guard that pops it once dropped, see [`Path::scope`].
+    fn push_scoped(&mut self, element: PathElement) -> ScopedPath<'_> {
+        let mut scope = self.scope();
+        scope.push(element);
+        scope
+    }
+}
+
+/// Guard returned by [`Path::scope`] and [`Path::push_scoped`], keeps the path
+/// the comparison is at correct even when it returns early.
+struct ScopedPath<'a> {
+    path: &'a mut Path,
+    len: usize,
+}
+
+impl Deref for ScopedPath<'_> {
+    type Target = Path;
+
+    fn deref(&self) -> &Self::Target {
+        self.path
+    }
+}
+
+impl DerefMut for ScopedPath<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.path
+    }
+}
+
+impl Drop for ScopedPath<'_> {
+    fn drop(&mut self) {
+        self.path.truncate(self.len);
+    }
+}
+
+/// `inside` and `leads_to` tell whether `path` may continue past the end of `pattern`
+/// or end before it respectively.
+fn elements_match(pattern: &[PathElement], path: &[PathElement], inside: bool, leads_to: bool) -> bool {
+    match (pattern.split_first(), path.split_first()) {
+        (None, None) => true,
+        (Some((PathElement::AnyDepth, rest)), _) => {
+            elements_match(rest, path, inside, leads_to)
+                || (!path.is_empty() && elements_match(pattern, &path[1..], inside, leads_to))
+        }
+        (None, Some(_)) => inside,
+        (Some(_), None) => leads_to,
+        (Some((a, pattern)), Some((b, path))) => a.matches(b) && elements_match(pattern, path, inside, leads_to),
+    }
+}
+
+impl DerefMut for Path {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl From<Vec<PathElement>> for Path {
+    fn from(value: Vec<PathElement>) -> Self {
+        Self(value)
+    }
+}
+
+impl FromStr for Path {
+    type Err = PathParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_element_path(s).map(Path)
+    }
+}
+
+impl TryFrom<&str> for Path {
+    type Error = PathParseError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+/// Formats the path in the same syntax [`Path::from_str`] parses, e.g. `users.[1].age`.
+impl fmt::Display for Path {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (idx, elem) in self.iter().enumerate() {
+            if idx > 0 {
+                f.write_str(".")?;
+            }
+            write!(f, "{}", elem)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Keys containing `.`, `[`, `]`, `'`, `"` or `\` and the `**` key are quoted, e.g. `'a.b'`,
+/// with `'` and `\` escaped by a backslash, e.g. `'it\'s'`, so they can be parsed back.
+impl fmt::Display for PathElement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PathElement::Key(key) if key == "**" || key.contains(['.', '[', ']', '\'', '"', '\\']) => {
+                write!(f, "'{}'", key.replace('\\', "\\\\").replace('\'', "\\'"))
+            }
+            PathElement::Key(key) => f.write_str(key),
+            PathElement::ArrayIndex(ArrayIndex::Index(idx)) => write!(f, "[{}]", idx),
+            PathElement::ArrayIndex(ArrayIndex::All) => f.write_str("[_]"),
+            PathElement::KeyPattern(pattern) => write!(f, "~'{}'", pattern.as_str().replace('\'', "\\'")),
+            PathElement::AnyDepth => f.write_str("**"),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "datetime")]
+    use std::time::Duration;
+    use serde_json::json;
+    use crate::{ArrayAlignment, ArrayDifference, ArrayIndex, ArrayOp, ArrayOutput, CompareError, DiffBuilder, DiffBuilderError, DiffMode, Difference, DifferenceKind, EntryDifference, IgnorePathBuilder, IgnorePathCondition, KeyOrder, LeafDifference, Map, NumberKind, Path, PathElement, ScalarDifference, Type, WhitespaceMode};
+
+    #[test]
+    fn ignore_paths_setter() {
+        let ignore_path = IgnorePathBuilder::default()
+            .path("users.[_].status".parse().unwrap())
+            .condition(IgnorePathCondition::ValueEquals(json!("archived")))
+            .build()
+            .unwrap();
+        assert_eq!(ignore_path.path.to_string(), "users.[_].status");
+        assert!(!ignore_path.ignore_missing);
+        assert_eq!(ignore_path.conditions, vec![IgnorePathCondition::ValueEquals(json!("archived"))]);
+
+        let diff = DiffBuilder::default()
+            .ignore_paths(vec![ignore_path])
+            .source(json!({"users": [{"status": "active"}, {"status": "active"}]}))
+            .target(json!({"users": [{"status": "archived"}, {"status": "deleted"}]}))
+            .build()
+            .unwrap()
+            .compare()
+            .unwrap();
+        let paths: Vec<_> = diff.flatten().into_iter().map(|(path, _)| path.to_string()).collect();
+        assert_eq!(paths, vec!["users.[1].status"]);
+    }
+
+    #[test]
+    fn ignore_with_value_condition() {
+        let compare = |source, target| {
+            DiffBuilder::default()
+                .ignore_path_with_condition("status", IgnorePathCondition::ValueEquals(json!("archived")))
+                .ignore_path_with_condition("kind", IgnorePathCondition::ValueIn(vec![json!(1), json!(2)]))
+                .source(source)
+                .target(target)
+                .build()
+                .unwrap()
+                .compare()
+        };
+
+        assert!(compare(json!({"status": "active", "kind": 0}), json!({"status": "archived", "kind": 2})).is_none());
+        assert!(compare(json!({"kind": 0}), json!({"status": "archived", "kind": 1})).is_none());
+
+        let diff = compare(json!({"status": "active", "kind": 0}), json!({"status": "deleted", "kind": 3})).unwrap();
+        let paths: Vec<_> = diff.flatten().into_iter().map(|(path, _)| path.to_string()).collect();
+        assert_eq!(paths, vec!["kind", "status"]);
+    }
+
+    #[test]
+    fn ignore_with_sibling_condition() {
+        let sibling_equals = |sibling: &str, value| IgnorePathCondition::SiblingEquals { sibling: sibling.to_string(), value };
+        let diff = DiffBuilder::default()
+            .ignore_path_with_condition("items.[_].price", sibling_equals("currency", json!("EUR")))
+            .ignore_path_with_condition("items.[_].note", sibling_equals("draft", json!(true)))
+            .source(json!({"items": [
+                {"currency": "EUR", "price": 1},
+                {"currency": "USD", "price": 1},
+                {"draft": true, "note": "a"},
+                {"draft": false, "note": "a"},
+            ]}))
+            .target(json!({"items": [
+                {"currency": "EUR", "price": 2},
+                {"currency": "USD", "price": 2},
+                {"draft": true},
+                {"draft": false},
+            ]}))
+            .build()
+            .unwrap()
+            .compare()
+            .unwrap();
+
+        let paths: Vec<_> = diff.flatten().into_iter().map(|(path, _)| path.to_string()).collect();
+        assert_eq!(paths, vec!["items.[1].price", "items.[3].note"]);
+    }
+
+    #[test]
+    #[cfg(feature = "rhai")]
+    fn ignore_with_rhai_current_path_string() {
+        let script = r#"current_path_string == "users.[1].age""#;
+        let diff = DiffBuilder::default()
+            .ignore_path_with_condition("users.[_].age", IgnorePathCondition::Rhai(script.to_string()))
+            .source(json!({"users": [{"age": 1}, {"age": 2}]}))
+            .target(json!({"users": [{"age": 3}, {"age": 4}]}))
+            .build()
+            .unwrap()
+            .compare()
+            .unwrap();
+        let paths: Vec<_> = diff.flatten().into_iter().map(|(path, _)| path.to_string()).collect();
+        assert_eq!(paths, vec!["users.[0].age"]);
+    }
+
+    #[test]
+    #[cfg(feature = "rhai")]
+    fn ignore_with_rhai_values() {
+        let script = r#"source_value != target_value && target_value == "archived""#;
+        let compare = |source, target| {
+            DiffBuilder::default()
+                .ignore_path_with_condition("status", IgnorePathCondition::Rhai(script.to_string()))
+                .source(source)
+                .target(target)
+                .build()
+                .unwrap()
+                .compare()
+        };
+
+        assert!(compare(json!({"status": "active"}), json!({"status": "archived"})).is_none());
+        assert!(compare(json!({}), json!({"status": "archived"})).is_none());
+        assert!(compare(json!({"status": "active"}), json!({"status": "deleted"})).is_some());
+        assert!(compare(json!({"status": "active"}), json!({})).is_some());
+    }
+
+    #[test]
+    #[cfg(feature = "rhai")]
+    fn ignore_with_rhai_condition() {
+        let obj1 = json!({
+            "users": [
+                {
+                    "name": "Joe",
+                    "age": 43,
+                },
+                {
+                    "name": "Ana",
+                    "age": 33,
+                    "animals": {
+                        "type": "dog"
+                    }
+                },
+            ]
+        });
+
+        let obj2 = json!({
+            "users": [
+                {
+                    "name": "Joe",
+                    "age": 43,
+                },
+                {
+                    "name": "Ana",
+                    "age": 33,
+                    "animals": {
+                        "type": "cat"
+                    }
+                },
+            ]
+        });
+
+        let script = r#"
+        let res = target.value_by_path("users.[_].age", curr_path);
+        res == 33
+        "#;
+
+        let diff = DiffBuilder::default()
+            .source(obj1)
+            .target(obj2)
+            .ignore_path_with_condition("users.[_].animals.type", IgnorePathCondition::Rhai(script.to_string()))
+            .build();
+        let diff = diff.unwrap().compare();
+
+        assert!(diff.is_none(), "{:?}", diff);
+    }
+
+    #[test]
+    fn ignore_source_missing() {
+        let obj1 = json!({
+            "name": "John Doe",
+        });
+
+        let obj2 = json!({
+            "name": "John Doe",
+            "age": 30
+        });
+
+        let diff = DiffBuilder::default()
+            .source(obj1)
+            .target(obj2)
+            .ignore_path_with_missing("age", true)
+            .build();
+        let diff = diff.unwrap().compare();
+
+        assert!(diff.is_none(), "{:?}", diff);
+    }
+
+    #[test]
+    fn equal_objects() {
+        let obj1 = json!({
+            "string": "b",
+            "int": 1,
+            "float": 1.0,
+            "bool": true,
+            "int_array": [1, 2, 3],
+            "float_array": [1.0, 2.0, 3.0],
+            "bool_array": [true, false, false],
+            "string_array": ["foo", "bar"],
+            "empty_array": [],
+            "null": null,
+            "object": {
+                "string": "c",
+                "int": 1,
+                "float": 1.0,
+                "bool": true,
+                "array": [1, 2, 3],
+                "null": null,
+                "object": {
+                    "string": "d",
+                }
+            },
+        });
+
+        let obj2 = json!({
+            "string": "b",
+            "int": 1,
+            "float": 1.0,
+            "bool": true,
+            "int_array": [1, 2, 3],
+            "float_array": [1.0, 2.0, 3.0],
+            "bool_array": [true, false, false],
+            "string_array": ["foo", "bar"],
+            "empty_array": [],
+            "null": null,
+            "object": {
+                "string": "c",
+                "int": 1,
+                "float": 1.0,
+                "bool": true,
+                "array": [1, 2, 3],
+                "null": null,
+                "object": {
+                    "string": "d",
+                }
+            },
+        });
+
+        let diff = DiffBuilder::default().source(obj1).target(obj2).build().unwrap();
+        let diff = diff.compare();
+        assert_eq!(true, diff.is_none(), "diff should be None, but got: {:?}", diff);
+    }
+
+
+    #[test]
+    fn ignore_fields() {
+        let user_1 = json!({
+            "user": "John",
+            "address": {
+                "city": "Astana",
+                "zip": 123,
+            },
+            "animals": ["dog", "cat"],
+            "object_array": [{"a": "b", "c": "d"}],
+            "optional_array": [],
+            "target_missing_value": 1,
+        });
+
+        let user_2 = json!({
+            "user": "Joe",
+            "address": {
+                "city": "Boston",
+                "zip": 312,
+            },
+            "animals": ["dog", "cat"],
+            "object_array": [{"a": "3", "c": "d"}],
+            "optional_array": null,
+        });
+
+        let diff = DiffBuilder::default()
+            .ignore_path("user")
+            .ignore_path("address.city")
+            .ignore_path("address.zip")
+            .ignore_path("object_array.[_].a")
+            .ignore_path_with_missing("target_missing_value", true)
+            .equate_empty_arrays(true)
+            .source(user_1)
+            .target(user_2)
+            .build()
+            .unwrap();
+
+        let diff = diff.compare();
+
+        assert_eq!(true, diff.is_none(), "diff should be None, but got: {:?}", diff);
+    }
+
+    #[test]
+    fn unordered_array() {
+        let diff = DiffBuilder::default()
+            .unordered_array("users.[_].roles")
+            .source(json!({"users": [{"roles": ["admin", "dev", "dev"], "ids": [1, 2]}]}))
+            .target(json!({"users": [{"roles": ["dev", "ops", "admin"], "ids": [2, 1]}]}))
+            .build()
+            .unwrap()
+            .compare()
+            .unwrap();
+        let paths: Vec<_> = diff.flatten().into_iter().map(|(path, _)| path.to_string()).collect();
+        assert_eq!(paths, vec!["users.[0].ids.[0]", "users.[0].ids.[1]", "users.[0].roles.[1]", "users.[0].roles.[2]"]);
+
+        let leaves = diff.flatten();
+        assert!(matches!(leaves[2].1, LeafDifference::Missing(value) if value == &json!("ops")));
+        assert!(matches!(leaves[3].1, LeafDifference::Extra(value) if value == &json!("dev")));
+    }
+
+    #[test]
+    fn ignore_value_type() {
+        let diff = DiffBuilder::default()
+            .ignore_value_type(Type::Bool)
+            .source(json!({"flags": {"beta": true, "old": false}, "name": "a", "ids": [true, 1], "enabled": "yes"}))
+            .target(json!({"flags": {"beta": false, "new": true}, "name": "b", "ids": [false, 2], "enabled": true}))
+            .build()
+            .unwrap()
+            .compare()
+            .unwrap();
+        assert_eq!(diff.to_text(), ["~ ids.[1]: 1 => 2", "~ name: \"a\" => \"b\""].join("\n"));
+    }
+
+    #[test]
+    fn ignore_path_kind() {
+        let diff = DiffBuilder::default()
+            .ignore_path_kind("items.[_].id", DifferenceKind::Type)
+            .ignore_path_kind("meta", DifferenceKind::Missing)
+            .ignore_path_kind("meta.a", DifferenceKind::Extra)
+            .ignore_path_kind("tags", DifferenceKind::Array)
+            .source(json!({"items": [{"id": 1}, {"id": 2}], "meta": {"a": 1}, "tags": [1, 2]}))
+            .target(json!({"items": [{"id": "1"}, {"id": 3}], "meta": {"b": 1}, "tags": [1]}))
+            .build()
+            .unwrap()
+            .compare()
+            .unwrap();
+        assert_eq!(diff.to_text(), ["~ items.[1].id: 2 => 3", "+ meta.b: 1"].join("\n"));
+
+        let build = || {
+            DiffBuilder::default()
+                .ignore_path_kind("address", DifferenceKind::Object)
+                .ignore_path_kind("tags", DifferenceKind::Array)
+                .source(json!({"address": {"city": "A"}, "tags": [{"a": 1}]}))
+                .target(json!({"address": {"city": "B"}, "tags": [{"a": 2}]}))
+                .build()
+                .unwrap()
+        };
+        assert!(build().compare().is_none());
+        assert!(build().equals());
+        let mut events = vec![];
+        build().compare_with(|event| events.push(event));
+        assert!(events.is_empty(), "{:?}", events);
+    }
+
+    #[test]
+    fn equate_empty_objects() {
+        let compare = |builder: &mut DiffBuilder| {
+            builder
+                .source(json!({"a": {}, "b": null, "c": [], "d": {"x": 1}}))
+                .target(json!({"a": null, "b": {}, "c": null, "d": null}))
+                .build()
+                .unwrap()
+                .compare()
+                .unwrap()
+                .flatten()
+                .into_iter()
+                .map(|(path, _)| path.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(compare(DiffBuilder::default().equate_empty_objects(true)), vec!["c", "d"]);
+        assert_eq!(compare(DiffBuilder::default().equate_empty_collections(true)), vec!["d"]);
+    }
+
+    #[test]
+    #[cfg(feature = "datetime")]
+    fn try_compare() {
+        let diff = DiffBuilder::default()
+            .approx_date_time_eq_duration(Duration::from_secs(1))
+            .source(json!({"ts": "2023-07-25T15:30:05Z", "float": 1.0}))
+            .target(json!({"ts": "2023-07-25T15:30:00Z", "float": 1}))
+            .build()
+            .unwrap()
+            .try_compare()
+            .unwrap()
+            .unwrap();
+        let Difference::Object { different_entries, .. } = diff else { panic!("{:?}", diff) };
+        assert_eq!(different_entries.0.len(), 1);
+        assert_eq!(different_entries.0[0].0, "ts");
+
+        let err = CompareError::NumberNotRepresentable {
+            path: vec![PathElement::Key("a".to_string())].into(),
+            number: 1.into(),
+        };
+        assert_eq!(err.to_string(), "number 1 at '/a' can't be represented as f64");
+    }
+
+    #[test]
+    fn deeply_nested() {
+        fn nested(depth: usize, leaf: serde_json::Value) -> serde_json::Value {
+            let mut value = leaf;
+            for i in 0..depth {
+                // `json!` would serialize the nested value recursively
+                value = if i % 2 == 0 {
+                    serde_json::Value::Array(vec![value])
+                } else {
+                    serde_json::Value::Object(serde_json::Map::from_iter([("a".to_string(), value)]))
+                };
+            }
+            value
+        }
+
+        // Diff is built with small values, because the builder clones them recursively.
+        let compare = |source, target| {
+            let mut diff = DiffBuilder::default().source(json!(null)).target(json!(null)).build().unwrap();
+            diff.source = source;
+            diff.target = target;
+            let difference = diff.compare_ref();
+            // Dropping the values is recursive.
+            std::mem::forget(diff);
+            difference
+        };
+
+        assert!(compare(nested(100_000, json!(1)), nested(100_000, json!(1))).is_none());
+
+        let diff = compare(nested(100_000, json!(1)), nested(100_000, json!(2)));
+        assert!(diff.is_some());
+        // Dropping the difference is recursive.
+        std::mem::forget(diff);
+    }
+
+    #[test]
+    fn custom_comparator() {
+        let diff = DiffBuilder::default()
+            .custom_comparator("version", |source, target| {
+                let major = |value: &serde_json::Value| value.as_str().and_then(|v| v.split('.').next().map(str::to_string));
+                major(source) == major(target)
+            })
+            .custom_comparator("items.[_]", |source, target| source.get("id") == target.get("id"))
+            .source(json!({"version": "1.2.0", "items": [{"id": 1, "n": 1}, {"id": 2}], "other": "1.0.0"}))
+            .target(json!({"version": "1.3.1", "items": [{"id": 1, "n": 2}, {"id": 3}], "other": "1.0.1"}))
+            .build()
+            .unwrap()
+            .compare()
+            .unwrap();
+
+        let leaves = diff.flatten();
+        let paths = leaves.iter().map(|(path, _)| path.to_string()).collect::<Vec<_>>();
+        assert_eq!(paths, vec!["items.[1]", "other"]);
+        assert!(matches!(leaves[0].1, LeafDifference::Type { source_type: Type::Object, target_type: Type::Object, .. }));
+
+        let diff = DiffBuilder::default()
+            .custom_comparator("version", |_, _| false)
+            .source(json!({"version": "1.0.0"}))
+            .target(json!({"version": "1.0.0"}))
+            .build()
+            .unwrap()
+            .compare()
+            .unwrap();
+        assert_eq!(diff.to_text(), "~ version: \"1.0.0\" => \"1.0.0\"");
+    }
+
+    #[test]
+    fn additive_mode() {
+        let source = json!({"a": 1, "b": [1, 2], "c": {"d": 1}, "e": [{"id": 1}], "f": [1, 3], "g": 1});
+        let target = json!({"a": 1, "b": [1, 2, 3], "c": {"d": 1, "x": 1}, "e": [{"id": 2}, {"id": 1}], "f": [0, 1, 2], "new": 1});
+        let paths = |mode, array_alignment| {
+            let diff = DiffBuilder::default()
+                .mode(mode)
+                .array_alignment(array_alignment)
+                .array_key("e", "id")
+                .source(source.clone())
+                .target(target.clone())
+                .build()
+                .unwrap()
+                .compare()
+                .unwrap();
+            diff.flatten().into_iter().map(|(path, _)| path.to_string()).collect::<Vec<_>>()
+        };
+
+        assert_eq!(paths(DiffMode::Additive, ArrayAlignment::Index), vec!["f.[0]", "f.[1]", "g"]);
+        assert_eq!(paths(DiffMode::Additive, ArrayAlignment::Lcs), vec!["f.[1]", "g"]);
+        assert_eq!(paths(DiffMode::Full, ArrayAlignment::Index), vec![
+            "b.[2]", "c.x", "e.[0]", "f.[0]", "f.[1]", "f.[2]", "g", "new",
+        ]);
+    }
+
+    #[test]
+    fn sort_output() {
+        let source = json!({"b": 1, "d": 1, "c": {"z": 1, "y": 1}});
+        let target = json!({"b": 2, "a": 1, "c": {"z": 2, "x": 1}});
+        let paths = |sort_output| {
+            let diff = DiffBuilder::default()
+                .sort_output(sort_output)
+                .source(source.clone())
+                .target(target.clone())
+                .build()
+                .unwrap()
+                .compare()
+                .unwrap();
+            diff.flatten().into_iter().map(|(path, _)| path.to_string()).collect::<Vec<_>>()
+        };
+
+        assert_eq!(paths(false), vec!["b", "c.y", "c.z", "c.x", "d", "a"]);
+        assert_eq!(paths(true), vec!["a", "b", "c.x", "c.y", "c.z", "d"]);
+    }
+
+    #[test]
+    #[cfg(feature = "float-approx")]
+    fn equals() {
+        let equals = |source, target| {
+            DiffBuilder::default()
+                .ignore_path("ignored")
+                .approx_float_eq_epsilon(0.01)
+                .source(source)
+                .target(target)
+                .build()
+                .unwrap()
+                .equals()
+        };
+
+        assert!(equals(json!({"a": [1, {"b": 1.0}], "ignored": 1}), json!({"a": [1, {"b": 1.001}], "ignored": 2})));
+        assert!(!equals(json!({"a": [1, {"b": 1.0}]}), json!({"a": [1, {"b": 2.0}]})));
+        assert!(!equals(json!({"a": [1, 2]}), json!({"a": [1]})));
+        assert!(!equals(json!({"a": 1, "b": 2}), json!({"a": 1})));
+        assert!(!equals(json!({"a": 1}), json!({"a": 1, "b": 2})));
+    }
+
+    #[test]
+    fn build_errors() {
+        let err = DiffBuilder::default().target(json!(1)).build().unwrap_err();
+        assert_eq!(err, DiffBuilderError::UninitializedField("source"));
+        assert_eq!(err.to_string(), "source value is required");
+
+        let err = DiffBuilder::default()
+            .ignore_path("users.[_.age")
+            .only_path("users")
+            .source(json!(1))
+            .target(json!(1))
+            .build()
+            .unwrap_err();
+        assert!(matches!(&err, DiffBuilderError::InvalidPath { path, error } if path == "users.[_.age" && error.offset == 8));
+        assert_eq!(err.to_string(), "invalid path 'users.[_.age': Unexpected '.' in brackets at offset 8");
+    }
+
+    #[test]
+    fn serializable_values() {
+        #[derive(serde::Serialize)]
+        struct User {
+            name: String,
+            age: u32,
+        }
+
+        let diff = DiffBuilder::default()
+            .source_serializable(&User { name: "Joe".to_string(), age: 31 })
+            .target_serializable(&User { name: "Joe".to_string(), age: 32 })
+            .build()
+            .unwrap()
+            .compare()
+            .unwrap();
+        assert_eq!(diff.to_text(), "~ age: 31 => 32");
+
+        let invalid = std::collections::HashMap::from([((1, 2), 3)]);
+        let err = DiffBuilder::default()
+            .source_serializable(&invalid)
+            .target(json!({}))
+            .build()
+            .unwrap_err();
+        assert!(matches!(err, DiffBuilderError::Serialization { field: "source", .. }));
+    }
+
+    #[test]
+    #[cfg(feature = "json5")]
+    fn json5() {
+        let diff = DiffBuilder::default()
+            .source_json5("{\n  // port of the server\n  port: 8080,\n  hosts: ['a', 'b',],\n}")
+            .target(json!({"port": 8081, "hosts": ["a", "b"]}))
+            .build()
+            .unwrap()
+            .compare()
+            .unwrap();
+        assert_eq!(diff.to_text(), "~ port: 8080 => 8081");
+
+        let err = DiffBuilder::default()
+            .source(json!({}))
+            .target_json5("{port: }")
+            .build()
+            .unwrap_err();
+        assert!(matches!(err, DiffBuilderError::Parse { field: "target", .. }));
+    }
+
+    #[test]
+    fn try_ignore_path() {
+        let mut builder = DiffBuilder::default();
+        assert!(builder.try_ignore_path("users.[_.age").is_err());
+
+        let diff = builder
+            .try_ignore_path("users.[_].age")
+            .unwrap()
+            .source(json!({"users": [{"age": 1}]}))
+            .target(json!({"users": [{"age": 2}]}))
+            .build()
+            .unwrap()
+            .compare();
+        assert!(diff.is_none());
+    }
+
+    #[test]
+    fn max_reported_array_elements() {
+        let compare = |source, target| {
+            DiffBuilder::default()
+                .max_reported_array_elements(2)
+                .source(source)
+                .target(target)
+                .build()
+                .unwrap()
+                .compare()
+                .unwrap()
+        };
+
+        let diff = compare(json!([1]), json!([1, 2, 3, 4, 5]));
+        let Difference::Array(ArrayDifference::Shorter { missing_elements, truncated_count, .. }) = &diff else {
+            panic!("expected shorter array, got {:?}", diff);
+        };
+        assert_eq!(missing_elements, &vec![json!(2), json!(3)]);
+        assert_eq!(*truncated_count, 2);
+        assert_eq!(serde_json::to_value(&diff).unwrap()["truncated_count"], json!(2));
+        assert!(diff.apply(&mut json!([1])).is_err());
+
+        let diff = compare(json!([1, 2, 3, 4]), json!([1]));
+        let Difference::Array(ArrayDifference::Longer { extra_elements, truncated_count, .. }) = &diff else {
+            panic!("expected longer array, got {:?}", diff);
+        };
+        assert_eq!(extra_elements, &vec![json!(2), json!(3)]);
+        assert_eq!(*truncated_count, 1);
+        assert_eq!(serde_json::to_value(&diff).unwrap()["extra_length"], json!(3));
+        let mut source = json!([1, 2, 3, 4]);
+        diff.apply(&mut source).unwrap();
+        assert_eq!(source, json!([1]));
+
+        let diff = compare(json!([1]), json!([1, 2]));
+        assert!(serde_json::to_value(&diff).unwrap().get("truncated_count").is_none());
+    }
+
+    #[test]
+    fn max_entry_differences() {
+        let diff = DiffBuilder::default()
+            .max_entry_differences(2)
+            .sort_output(true)
+            .source(json!({"d": 1, "a": 1, "c": {"x": 1, "y": 1, "z": 1}, "b": 1}))
+            .target(json!({"a": 2, "b": 2, "c": {"x": 2, "y": 2, "z": 2}, "e": 1}))
+            .build()
+            .unwrap()
+            .compare()
+            .unwrap();
+
+        let Difference::Object { different_entries, truncated_count, .. } = &diff else { panic!("{:?}", diff) };
+        let keys: Vec<_> = different_entries.0.iter().map(|(key, _)| key.as_str()).collect();
+        assert_eq!(keys, vec!["a", "b"]);
+        assert_eq!(*truncated_count, 3);
+        assert_eq!(serde_json::to_value(&diff).unwrap()["truncated_count"], json!(3));
+        assert!(diff.apply(&mut json!({"d": 1, "a": 1, "c": {}, "b": 1})).is_err());
+
+        let diff = DiffBuilder::default()
+            .max_entry_differences(2)
+            .source(json!({"a": {"x": 1, "y": 1, "z": 1}}))
+            .target(json!({"a": {"x": 1, "y": 2, "z": 2}}))
+            .build()
+            .unwrap()
+            .compare()
+            .unwrap();
+        assert_eq!(diff.to_text(), ["~ a.y: 1 => 2", "~ a.z: 1 => 2"].join("\n"));
+        assert!(serde_json::to_value(&diff).unwrap().get("truncated_count").is_none());
+    }
+
+    #[test]
+    fn number_kind() {
+        let diff = DiffBuilder::default()
+            .source(json!({"a": 1, "b": 1.5, "c": 1, "d": 2.5}))
+            .target(json!({"a": 2, "b": 2.5, "c": 1.5, "d": 2}))
+            .build().unwrap()
+            .compare()
+            .unwrap();
+        let kinds: Vec<_> = diff.flatten()
+            .into_iter()
+            .map(|(_, leaf)| match leaf {
+                LeafDifference::Scalar(ScalarDifference::Number { number_kind, .. }) => *number_kind,
+                leaf => panic!("{:?}", leaf),
+            })
+            .collect();
+        assert_eq!(kinds, vec![NumberKind::Integer, NumberKind::Float, NumberKind::IntegerToFloat, NumberKind::FloatToInteger]);
+
+        let reversed = serde_json::to_value(diff.reverse()).unwrap();
+        assert_eq!(reversed["different_entries"]["c"]["value_diff"]["number_kind"], json!("float_to_integer"));
+    }
+
+    #[test]
+    #[cfg(feature = "float-approx")]
+    fn non_finite_numbers() {
+        let diff = |nan_equals_nan| {
+            DiffBuilder::default()
+                .approx_float_eq_epsilon(0.1)
+                .approx_float_abs_epsilon(1.0)
+                .nan_equals_nan(nan_equals_nan)
+                .source(json!(null))
+                .target(json!(null))
+                .build()
+                .unwrap()
+        };
+
+        assert!(!diff(false).floats_eq(f64::NAN, f64::NAN, 0.1));
+        assert!(diff(true).floats_eq(f64::NAN, f64::NAN, 0.1));
+        assert!(!diff(true).floats_eq(f64::NAN, 1.0, 0.1));
+        assert!(diff(false).floats_eq(f64::INFINITY, f64::INFINITY, 0.1));
+        assert!(!diff(false).floats_eq(f64::INFINITY, f64::NEG_INFINITY, 0.1));
+        assert!(!diff(false).floats_eq(f64::INFINITY, f64::MAX, 0.1));
+        assert!(diff(false).floats_eq(1.0, 1.5, 0.1));
+    }
+
+    #[test]
+    fn coerce_scalars() {
+        let compare = |source, target| {
+            DiffBuilder::default()
+                .coerce_scalars(true)
+                .source(source)
+                .target(target)
+                .build()
+                .unwrap()
+                .compare()
+        };
+
+        assert!(compare(json!({"a": "5", "b": 2.5, "c": "true", "d": false}), json!({"a": 5, "b": "2.5", "c": true, "d": "false"})).is_none());
+        assert!(compare(json!("5.0"), json!(5)).is_none());
+
+        for (source, target) in [(json!("5"), json!(6)), (json!("five"), json!(5)), (json!("True"), json!(true)), (json!("1"), json!(true))] {
+            let diff = compare(source, target);
+            assert!(matches!(diff, Some(Difference::Type { .. })), "{:?}", diff);
+        }
+    }
+
+    #[test]
+    fn ignore_subtree() {
+        let diff = DiffBuilder::default()
+            .ignore_subtree("metadata")
+            .ignore_subtree("items.[0]")
+            .ignore_subtree("users.[_].audit")
+            .source(json!({"metadata": {"a": 1, "tags": [1]}, "items": [{"a": 1}, {"a": 1}], "users": [{"audit": {"at": 1}, "n": 1}]}))
+            .target(json!({"items": [{"a": 2}, {"a": 2}], "users": [{"audit": [], "n": 2}]}))
+            .build()
+            .unwrap()
+            .compare()
+            .unwrap();
+        assert_eq!(diff.to_text(), ["~ items.[1].a: 1 => 2", "~ users.[0].n: 1 => 2"].join("\n"));
+
+        let diff = DiffBuilder::default().ignore_subtree("**").source(json!(1)).target(json!(2)).build().unwrap();
+        assert!(diff.compare().is_none());
+    }
+
+    #[test]
+    fn parse_numeric_strings() {
+        let compare = |source, target| {
+            DiffBuilder::default()
+                .parse_numeric_strings(true)
+                .source(source)
+                .target(target)
+                .build()
+                .unwrap()
+                .compare()
+        };
+
+        assert!(compare(json!({"a": "42", "b": 2.5, "c": "1e2"}), json!({"a": 42, "b": "2.50", "c": 100})).is_none());
+        let diff = compare(json!({"a": "42", "b": "true", "c": "x"}), json!({"a": 43, "b": true, "c": 1})).unwrap();
+        assert_eq!(diff.stats().type_changes, 3);
+    }
+
+    #[test]
+    fn array_extra_tolerance() {
+        let compare = |source, target| {
+            DiffBuilder::default()
+                .array_extra_tolerance("logs", 2)
+                .source(source)
+                .target(target)
+                .build()
+                .unwrap()
+                .compare()
+                .map(|diff| diff.to_text())
+        };
+
+        assert_eq!(compare(json!({"logs": [1, 2]}), json!({"logs": [1, 2, 3, 4]})), None);
+        assert_eq!(compare(json!({"logs": [1, 2]}), json!({"logs": [1, 5, 3]})), Some("~ logs.[1]: 2 => 5".to_string()));
+        assert_eq!(compare(json!({"logs": [1, 2]}), json!({"logs": [1, 2, 3, 4, 5]})), Some("+ logs.[2]: 3\n+ logs.[3]: 4\n+ logs.[4]: 5".to_string()));
+        assert_eq!(compare(json!({"logs": [1, 2]}), json!({"logs": [1]})), Some("- logs.[1]".to_string()));
+        assert_eq!(compare(json!({"other": [1]}), json!({"other": [1, 2]})), Some("+ other.[1]: 2".to_string()));
+    }
+
+    #[test]
+    fn parse_numeric_strings_in_arrays() {
+        let compare = |alignment| {
+            DiffBuilder::default()
+                .parse_numeric_strings(true)
+                .array_alignment(alignment)
+                .unordered_array("unordered")
+                .array_key("keyed", "id")
+                .source(json!({
+                    "list": ["1", 2, "3.0", "x"],
+                    "unordered": ["1", "2"],
+                    "keyed": [{"id": "1", "n": "5"}, {"id": 2.0, "n": 1}],
+                }))
+                .target(json!({
+                    "list": [1, "2", 3, 4],
+                    "unordered": [2, 1],
+                    "keyed": [{"id": "2", "n": "1"}, {"id": 1, "n": 6}],
+                }))
+                .build()
+                .unwrap()
+                .compare()
+                .unwrap()
+                .to_text()
+        };
+
+        for alignment in [ArrayAlignment::Index, ArrayAlignment::Lcs] {
+            let text = compare(alignment);
+            assert_eq!(text, [r#"~ keyed.[0].n: "5" => 6"#, r#"~ list.[3]: "x" => 4"#].join("\n"), "{:?}", alignment);
+        }
+    }
+
+    #[test]
+    fn ordered_object_keys() {
+        let diff = DiffBuilder::default().ordered_object_keys(true).source(json!({})).target(json!({})).build().unwrap();
+        let target = json!({"a": 1, "b": 2, "c": 3});
+        let target = target.as_object().unwrap();
+        let (a, b, c) = (&"a".to_string(), &"b".to_string(), &"c".to_string());
+        assert_eq!(diff.key_order(&[a, b, c], target), None);
+        assert_eq!(diff.key_order(&[a, c], target), None);
+        assert_eq!(diff.key_order(&[c, a], target), Some(KeyOrder {
+            source: vec!["c".to_string(), "a".to_string()],
+            target: vec!["a".to_string(), "c".to_string()],
+        }));
+
+        let difference = Difference::Object {
+            different_entries: Map(vec![]),
+            truncated_count: 0,
+            key_order: diff.key_order(&[b, a], target),
+        };
+        assert_eq!(difference.to_text(), r#"> (root): ["b","a"] => ["a","b"]"#);
+        assert_eq!(difference.reverse().to_text(), r#"> (root): ["a","b"] => ["b","a"]"#);
+        assert_eq!(difference.stats().reordered_objects, 1);
+        let mut source = json!({"b": 2, "a": 1, "c": 3});
+        difference.apply(&mut source).unwrap();
+        assert_eq!(source.to_string(), json!({"a": 1, "b": 2, "c": 3}).to_string());
+    }
+
+    #[test]
+    fn array_index_all_matching() {
+        let all: Path = "a.[_].c".parse().unwrap();
+        let first: Path = "a.[0].c".parse().unwrap();
+        assert_ne!(all, first);
+        assert!(all.matches(&first));
+        assert!(!first.matches(&all));
+        assert!(!first.matches(&"a.[1].c".parse().unwrap()));
+
+        let ignore_path = |path: &Path| IgnorePathBuilder::default().path(path.clone()).build().unwrap();
+        assert_ne!(ignore_path(&all), ignore_path(&first));
+
+        let diff = DiffBuilder::default()
+            .ignore_path("a.[0].c")
+            .ignore_path("a.[_].c")
+            .ignore_path("b.[0].c")
+            .source(json!({"a": [{"c": 1}, {"c": 1}], "b": [{"c": 1}, {"c": 1}]}))
+            .target(json!({"a": [{"c": 2}, {"c": 2}], "b": [{"c": 2}, {"c": 2}]}))
+            .build()
+            .unwrap();
+        assert_eq!(diff.compare_ref().unwrap().to_text(), "~ b.[1].c: 1 => 2");
+        assert!(diff.unused_ignore_paths().is_empty());
+    }
+
+    #[test]
+    fn path_macro() {
+        let path = crate::path!["users", 1, "age".to_string(), ArrayIndex::All];
+        assert_eq!(path, Path::from(vec![
+            PathElement::key("users"),
+            PathElement::index(1),
+            PathElement::Key("age".to_string()),
+            PathElement::ArrayIndex(ArrayIndex::All),
+        ]));
+        assert_eq!(path.to_string(), "users.[1].age.[_]");
+        assert_eq!(crate::path![], Path::default());
+    }
+
+    #[test]
+    fn path_from_elements() {
+        let mut path: Path = ["users", "tags"].into_iter().map(PathElement::key).collect();
+        assert_eq!(path, crate::path!["users", "tags"]);
+
+        path.extend([PathElement::index(0), PathElement::key("name")]);
+        assert_eq!(path.to_string(), "users.tags.[0].name");
+
+        let mut path = Path::default();
+        path.push_key("users").push_index(1).push_key("age");
+        assert_eq!(path, crate::path!["users", 1, "age"]);
+
+        let paths: Vec<Path> = (0..2).map(|idx| {
+            let mut path = Path::default();
+            path.push_key("items").push_index(idx);
+            path
+        }).collect();
+        assert_eq!(paths.iter().map(ToString::to_string).collect::<Vec<_>>(), ["items.[0]", "items.[1]"]);
+    }
+
+    #[test]
+    fn path_get() {
+        let value = json!({"users": [{"age": 31}, {"age": 33, "tags": ["a"]}]});
+        let get = |path: &str| path.parse::<Path>().unwrap().get(&value).cloned();
+
+        assert_eq!(get("users.[1].age"), Some(json!(33)));
+        assert_eq!(get("users.[1].tags.[0]"), Some(json!("a")));
+        assert_eq!(get("users.[0]"), Some(json!({"age": 31})));
+        assert_eq!(Path::default().get(&value), Some(&value));
+        assert_eq!(get("users.[2].age"), None);
+        assert_eq!(get("users.age"), None);
+        assert_eq!(get("users.[_].age"), None);
+        assert_eq!(get("**.age"), None);
+    }
+
+    #[test]
+    fn ignore_paths_of_nested_arrays() {
+        let source = json!({"a": [{"ts": 1, "x": 1, "b": [{"ts": 1, "y": 1}]}, {"ts": 1, "x": 2}], "c": {"ts": 1}});
+        let target = json!({"a": [{"ts": 2, "x": 1, "b": [{"ts": 2, "y": 2}]}, {"ts": 2, "x": 2}], "c": {"ts": 2}});
+
+        for array_alignment in [ArrayAlignment::Index, ArrayAlignment::Lcs] {
+            let builder = || {
+                let mut builder = DiffBuilder::default();
+                builder
+                    .array_alignment(array_alignment)
+                    .ignore_path("**.ts")
+                    .source(source.clone())
+                    .target(target.clone());
+                builder
+            };
+
+            let diff = builder().build().unwrap().compare().unwrap();
+            let paths: Vec<_> = diff.flatten().into_iter().map(|(path, _)| path.to_string()).collect();
+            assert_eq!(paths, vec!["a.[0].b.[0].y"], "{:?}", array_alignment);
+            assert!(!builder().build().unwrap().equals());
+
+            let equal_target = json!({"a": [{"ts": 2, "x": 1, "b": [{"ts": 2, "y": 1}]}, {"ts": 2, "x": 2}], "c": {"ts": 2}});
+            assert!(builder().target(equal_target).ignore_path("a.[_].b.[_].y").build().unwrap().equals());
+        }
+
+        let diff = DiffBuilder::default()
+            .array_alignment(ArrayAlignment::Lcs)
+            .ignore_path("a.[_].ts")
+            .source(json!({"a": [{"ts": 1, "x": 1}, {"ts": 1, "x": 2}]}))
+            .target(json!({"a": [{"ts": 2, "x": 1}, {"ts": 2, "x": 2}]}))
+            .build()
+            .unwrap();
+        assert!(diff.equals());
+    }
+
+    #[test]
+    fn unused_ignore_paths() {
+        let diff = DiffBuilder::default()
+            .ignore_path("users.[_].age")
+            .ignore_path("users.[_].adress")
+            .ignore_path_with_condition("name", IgnorePathCondition::ValueEquals(json!("Bob")))
+            .source(json!({"name": "Joe", "users": [{"age": 31}]}))
+            .target(json!({"name": "Jim", "users": [{"age": 32}]}))
+            .build()
+            .unwrap();
+        assert_eq!(diff.unused_ignore_paths().len(), 3);
+
+        diff.compare_ref();
+        let unused: Vec<_> = diff.unused_ignore_paths().iter().map(|p| p.path.to_string()).collect();
+        assert_eq!(unused, vec!["users.[_].adress"]);
+    }
+
+    #[test]
+    fn clone() {
+        let mut builder = DiffBuilder::default();
+        builder
+            .ignore_path("ts")
+            .custom_comparator("version", |a, b| a.as_str().map(|a| a.trim_start_matches('v')) == b.as_str())
+            .source(json!({"ts": 1, "version": "v1", "n": 1}));
+
+        let diff = builder.clone().target(json!({"ts": 2, "version": "1", "n": 1})).build().unwrap();
+        let other = builder.clone().target(json!({"ts": 2, "version": "2", "n": 2})).build().unwrap();
+        assert!(diff.clone().compare().is_none());
+        assert!(diff.compare().is_none());
+
+        let difference = other.compare().unwrap();
+        assert_eq!(difference.clone().reverse().reverse(), difference);
+        assert_eq!(difference.to_text(), ["~ n: 1 => 2", "~ version: \"v1\" => \"2\""].join("\n"));
+    }
+
+    #[test]
+    fn difference_eq() {
+        let diff = DiffBuilder::default()
+            .source(json!({"a": 1, "b": [true], "c": "x"}))
+            .target(json!({"a": 1.5, "b": [false, null], "d": "x"}))
+            .build()
+            .unwrap()
+            .compare();
+
+        let expected = Difference::Object {
+            different_entries: Map(vec![
+                ("a".to_string(), EntryDifference::Value {
+                    value_diff: Difference::Scalar(ScalarDifference::Number {
+                        source: 1.into(),
+                        target: serde_json::Number::from_f64(1.5).unwrap(),
+                        number_kind: NumberKind::IntegerToFloat,
+                    }),
+                }),
+                ("b".to_string(), EntryDifference::Value {
+                    value_diff: Difference::Array(ArrayDifference::Shorter {
+                        different_pairs: Some(Map(vec![(0, Difference::Scalar(ScalarDifference::Bool { source: true, target: false }))])),
+                        source_length: 1,
+                        missing_elements: vec![json!(null)],
+                        truncated_count: 0,
+                    }),
+                }),
+                ("c".to_string(), EntryDifference::Extra { value: json!("x") }),
+                ("d".to_string(), EntryDifference::Missing { value: json!("x") }),
+            ]),
+            truncated_count: 0,
+            key_order: None,
+        };
+        assert_eq!(diff, Some(expected));
+    }
+
+    #[test]
+    fn compare_ref() {
+        let diff = DiffBuilder::default()
+            .array_key("items", "id")
+            .source(json!({"name": "Joe", "items": [{"id": 1, "n": 1}, {"id": 2}], "tags": ["a"]}))
+            .target(json!({"name": "Joe", "items": [{"id": 2}, {"id": 1, "n": 2}], "tags": ["a", "b"], "age": 31}))
+            .build()
+            .unwrap();
+
+        let difference = diff.compare_ref();
+        assert!(difference.is_some());
+        assert_eq!(diff.compare_ref(), difference);
+        assert_eq!(diff.compare(), difference);
+    }
+
+    #[test]
+    fn max_depth() {
+        let compare = |max_depth| {
+            DiffBuilder::default()
+                .max_depth(max_depth)
+                .source(json!({"a": [{"b": 1}]}))
+                .target(json!({"a": [{"b": 2}]}))
+                .build()
+                .unwrap()
+                .try_compare()
+        };
+
+        assert!(compare(0).unwrap().is_some());
+        assert!(compare(3).unwrap().is_some());
+        let err = compare(2).unwrap_err();
+        assert_eq!(err, CompareError::MaxDepthExceeded { path: "a.[0].b".parse().unwrap(), max_depth: 2 });
+        assert_eq!(err.to_string(), "value at '/a/0/b' is nested deeper than the max depth of 2");
+    }
+
+    #[test]
+    fn only_paths() {
+        let diff = DiffBuilder::default()
+            .only_path("users.[_].name")
+            .only_paths(["address", "ignored"])
+            .ignore_path("ignored")
+            .source(json!({
+                "users": [{"name": "Joe", "age": 1}, {"name": "Ana", "age": 2}],
+                "address": {"city": "Astana"},
+                "ignored": 1,
+                "id": 1,
+            }))
+            .target(json!({
+                "users": [{"name": "Joe", "age": 3}, {"name": "Anna", "age": 4}],
+                "address": {"city": "Boston"},
+                "ignored": 2,
+                "email": "joe@example.com",
+            }))
+            .build()
+            .unwrap()
+            .compare()
+            .unwrap();
+
+        let paths = diff.flatten().into_iter().map(|(path, _)| path.to_string()).collect::<Vec<_>>();
+        assert_eq!(paths, vec!["address.city", "users.[1].name"]);
+    }
+
+    #[test]
+    fn ignore_key_pattern() {
+        let diff = DiffBuilder::default()
+            .ignore_path("~'_at$'")
+            .ignore_path("users.[_].~'^tmp_'")
+            .source(json!({
+                "created_at": "a",
+                "updated_at": "b",
+                "users": [{"tmp_id": 1, "name": "Joe", "created_at": 1}],
+            }))
+            .target(json!({
+                "created_at": "c",
+                "updated_at": "d",
+                "users": [{"tmp_id": 2, "name": "Joe", "created_at": 2}],
+            }))
+            .build()
+            .unwrap()
+            .compare()
+            .unwrap();
+
+        let paths = diff.flatten().into_iter().map(|(path, _)| path.to_string()).collect::<Vec<_>>();
+        assert_eq!(paths, vec!["users.[0].created_at"]);
+    }
+
+    #[test]
+    fn ignore_any_depth() {
+        let diff = DiffBuilder::default()
+            .ignore_path("**.metadata")
+            .ignore_path("users.**.id")
+            .source(json!({
+                "metadata": 1,
+                "id": 1,
+                "users": [{"id": 1, "pets": [{"id": 1, "metadata": {"a": 1}}]}],
+                "deep": {"a": {"b": {"metadata": "x"}}},
+            }))
+            .target(json!({
+                "metadata": 2,
+                "id": 2,
+                "users": [{"id": 2, "pets": [{"id": 2, "metadata": {"a": 2}}]}],
+                "deep": {"a": {"b": {"metadata": "y"}}},
+            }))
+            .build()
+            .unwrap()
+            .compare()
+            .unwrap();
+
+        let paths = diff.flatten().into_iter().map(|(path, _)| path.to_string()).collect::<Vec<_>>();
+        assert_eq!(paths, vec!["id"]);
+
+        let diff = DiffBuilder::default()
+            .only_path("**.name")
+            .source(json!({"id": 1, "user": {"id": 1, "name": "Joe"}}))
+            .target(json!({"id": 2, "user": {"id": 2, "name": "Ana"}}))
+            .build()
+            .unwrap()
+            .compare()
+            .unwrap();
+
+        let paths = diff.flatten().into_iter().map(|(path, _)| path.to_string()).collect::<Vec<_>>();
+        assert_eq!(paths, vec!["user.name"]);
+    }
+
+    #[test]
+    fn equate_null_and_missing() {
+        let source = json!({"a": null, "b": 1, "c": {"d": null}});
+        let target = json!({"b": 1, "c": {}, "e": null, "f": 0});
+
+        let diff = DiffBuilder::default()
+            .equate_null_and_missing(true)
+            .source(source.clone())
+            .target(target.clone())
+            .build()
+            .unwrap()
+            .compare()
+            .unwrap();
+        let paths = diff.flatten().into_iter().map(|(path, _)| path.to_string()).collect::<Vec<_>>();
+        assert_eq!(paths, vec!["f"]);
+
+        let diff = DiffBuilder::default().source(source).target(target).build().unwrap().compare().unwrap();
+        assert_eq!(diff.stats().total(), 4);
+    }
+
+    #[test]
+    fn equate_empty_collections_and_missing() {
+        let source = json!({"tags": [], "meta": {}, "items": [{"ids": []}], "name": "a"});
+        let target = json!({"items": [{}], "name": "a", "extra": [], "list": [1]});
+        let compare = |builder: &mut DiffBuilder| {
+            let diff = builder.source(source.clone()).target(target.clone()).build().unwrap().compare().unwrap();
+            diff.flatten().into_iter().map(|(path, _)| path.to_string()).collect::<Vec<_>>()
+        };
+
+        assert_eq!(compare(DiffBuilder::default().equate_null_and_missing(true).equate_empty_arrays(true)), ["meta", "list"]);
+        assert_eq!(compare(DiffBuilder::default().equate_null_and_missing(true).equate_empty_collections(true)), ["list"]);
+        assert_eq!(compare(DiffBuilder::default().equate_empty_collections(true)).len(), 5);
+    }
+
+    #[test]
+    #[cfg(feature = "float-approx")]
+    fn approx_float_eq() {
+        let obj1 = json!({
+            "float": 1.34
+        });
+
+        let obj2 = json!({
+            "float": 1.341
+        });
+
+        let diff = DiffBuilder::default()
+            .approx_float_eq_epsilon(0.001)
+            .source(obj1).target(obj2).build().unwrap();
+
+        let diff = diff.compare();
+
+        assert_eq!(true, diff.is_none(), "diff should be None, but got: {:?}", diff);
+    }
+
+    #[test]
+    #[cfg(feature = "float-approx")]
+    fn approx_float_eq_for_path() {
+        let diff = DiffBuilder::default()
+            .approx_float_eq_epsilon(0.01)
+            .approx_float_eq_epsilon_for_path("price", 0.0)
+            .approx_float_eq_epsilon_for_path("items.[_].ratio", 0.1)
+            .source(json!({"price": 10.001, "ratio": 0.5, "items": [{"ratio": 0.5}]}))
+            .target(json!({"price": 10.002, "ratio": 0.501, "items": [{"ratio": 0.55}]}))
+            .build()
+            .unwrap()
+            .compare()
+            .unwrap();
+
+        let paths = diff.flatten().into_iter().map(|(path, _)| path.to_string()).collect::<Vec<_>>();
+        assert_eq!(paths, vec!["price"]);
+    }
+
+    #[test]
+    #[cfg(feature = "float-approx")]
+    fn approx_float_abs_eq() {
+        let diff = DiffBuilder::default()
+            .approx_float_abs_epsilon(0.001)
+            .source(json!({"a": 0.0, "b": 100.0005}))
+            .target(json!({"a": 0.0001, "b": 100.0}))
+            .build().unwrap()
+            .compare();
+        assert!(diff.is_none(), "diff should be None, but got: {:?}", diff);
+
+        let diff = DiffBuilder::default()
+            .approx_float_eq_epsilon(0.00001)
+            .source(json!({"a": 0.0}))
+            .target(json!({"a": 0.0001}))
+            .build().unwrap()
+            .compare();
+        assert!(diff.is_some());
+    }
+
+    #[test]
+    #[cfg(feature = "float-approx")]
+    fn approx_float_eq_percent() {
+        let diff = DiffBuilder::default()
+            .approx_float_eq_percent(5.0)
+            .approx_float_eq_epsilon_for_path("exact", 0.0)
+            .source(json!({"revenue": 100.0, "loss": -2.0, "exact": 10.0, "growth": 100.0, "count": 100}))
+            .target(json!({"revenue": 104.5, "loss": -1.92, "exact": 10.4, "growth": 106.0, "count": 104}))
+            .build().unwrap()
+            .compare()
+            .unwrap();
+        assert_eq!(diff.to_text(), ["~ count: 100 => 104", "~ growth: 100.0 => 106.0"].join("\n"));
+    }
+
+    #[test]
+    #[cfg(feature = "float-approx")]
+    fn approx_float_eq_mixed_numbers() {
+        let compare = |builder: &mut DiffBuilder, source, target| builder.source(source).target(target).build().unwrap().compare();
+
+        let diff = compare(
+            DiffBuilder::default().approx_float_eq_epsilon(0.001),
+            json!([1, 2.0, 3, -0.0, 1e3, [4.0004]]),
+            json!([1.0, 2, 3.0003, 0, 1000, [4]]),
+        );
+        assert!(diff.is_none(), "diff should be None, but got: {:?}", diff);
+
+        let diff = compare(
+            DiffBuilder::default().approx_float_eq_epsilon(0.001).array_alignment(ArrayAlignment::Lcs),
+            json!([1, 2.0, 3]),
+            json!([1.0, 2, 3.0003]),
+        );
+        assert!(diff.is_none(), "diff should be None, but got: {:?}", diff);
+
+        let diff = compare(
+            DiffBuilder::default().approx_float_abs_epsilon(0.01).unordered_array("a"),
+            json!({"a": [1, 2.005, 3]}),
+            json!({"a": [3.001, 2, 1.0]}),
+        );
+        assert!(diff.is_none(), "diff should be None, but got: {:?}", diff);
+
+        // Integers are compared exactly unless `numbers_equal_across_types` is set.
+        let diff = compare(DiffBuilder::default().approx_float_eq_epsilon(1.0), json!([100, 100.0]), json!([101, 101]));
+        assert_eq!(diff.unwrap().to_text(), "~ [0]: 100 => 101");
+        let diff = compare(
+            DiffBuilder::default().approx_float_eq_epsilon(1.0).numbers_equal_across_types(true),
+            json!([100, 100.0]),
+            json!([101, 101]),
+        );
+        assert!(diff.is_none(), "diff should be None, but got: {:?}", diff);
+    }
+
+    #[test]
+    fn top_level_scalars() {
+        let build = |source, target| DiffBuilder::default().source(source).target(target).build().unwrap();
+
+        let diff = build(json!(1), json!(2)).compare().unwrap();
+        assert_eq!(diff, Difference::Scalar(ScalarDifference::Number {
+            source: 1.into(),
+            target: 2.into(),
+            number_kind: NumberKind::Integer,
+        }));
+        assert_eq!(diff.flatten()[0].0, Path::default());
+        assert_eq!(diff.to_text(), "~ (root): 1 => 2");
+        assert_eq!(diff.to_json_patch(), vec![json!({"op": "replace", "path": "", "value": 2})]);
+        assert_eq!(serde_json::to_value(diff.with_paths()).unwrap()["path"], json!(""));
+        let mut value = json!(1);
+        diff.apply(&mut value).unwrap();
+        assert_eq!(value, json!(2));
+
+        let diff = build(json!("a"), json!(1)).compare().unwrap();
+        assert_eq!(diff.kind(), DifferenceKind::Type);
+        assert_eq!(diff.flatten()[0].0, Path::default());
+        assert_eq!(diff.to_text(), "~ (root): \"a\" => 1");
+        assert_eq!(diff.reverse().to_text(), "~ (root): 1 => \"a\"");
+
+        let mut paths = vec![];
+        build(json!("a"), json!(1)).compare_with(|event| paths.push(event.path().clone()));
+        assert_eq!(paths, vec![Path::default()]);
+
+        assert!(build(json!(null), json!(null)).compare().is_none());
+        assert!(build(json!("a"), json!("a")).compare().is_none());
+    }
+
+    #[test]
+    fn trim_trailing_nulls() {
+        let compare = |source, target| {
+            DiffBuilder::default()
+                .trim_trailing_nulls(true)
+                .source(source)
+                .target(target)
+                .build()
+                .unwrap()
+                .compare()
+        };
+
+        assert!(compare(json!({"a": [1, 2, null]}), json!({"a": [1, 2]})).is_none());
+        assert!(compare(json!([null, null]), json!([])).is_none());
+        assert_eq!(compare(json!([1, null, 3]), json!([1, 2, 3, null])).unwrap().to_text(), "~ [1]: null => 2");
+        assert_eq!(compare(json!([1, null]), json!([1, 5])).unwrap().to_text(), "+ [1]: 5");
+        assert_eq!(compare(json!([1, 2, null]), json!([1, null])).unwrap().to_text(), "- [1]");
+    }
+
+    #[test]
+    fn min_numeric_change() {
+        let diff = DiffBuilder::default()
+            .min_numeric_change(1.0)
+            .source(json!({"count": 100, "price": 9.99, "total": 100, "balance": -5, "exact": 2}))
+            .target(json!({"count": 101, "price": 10.5, "total": 99.2, "balance": "-5", "exact": 3}))
+            .build()
+            .unwrap()
+            .compare()
+            .unwrap();
+        assert_eq!(diff.to_text(), ["~ balance: -5 => \"-5\"", "~ count: 100 => 101", "~ exact: 2 => 3"].join("\n"));
+
+        let diff = DiffBuilder::default()
+            .min_numeric_change(1.0)
+            .source(json!({"id": 9007199254740993_u64, "big": u64::MAX, "min": i64::MIN}))
+            .target(json!({"id": 9007199254740992_u64, "big": u64::MAX - 1, "min": i64::MIN + 1}))
+            .build()
+            .unwrap()
+            .compare()
+            .unwrap();
+        assert_eq!(diff.to_text(), [
+            "~ big: 18446744073709551615 => 18446744073709551614",
+            "~ id: 9007199254740993 => 9007199254740992",
+            "~ min: -9223372036854775808 => -9223372036854775807",
+        ].join("\n"));
+    }
+
+    #[test]
+    #[cfg(feature = "arbitrary_precision")]
+    fn arbitrary_precision_numbers() {
+        let compare = |source: &str, target: &str| {
+            DiffBuilder::default()
+                .source(serde_json::from_str(source).unwrap())
+                .target(serde_json::from_str(target).unwrap())
+                .build()
+                .unwrap()
+                .try_compare()
+                .unwrap()
+        };
 
-        if res.len() != self.len() {
-            return None
-        }
+        assert!(compare("1e400", "10.0e399").is_none());
+        assert!(compare("-1e400", "1e400").is_some());
+        assert!(compare("123456789012345678901234567890", "123456789012345678901234567890").is_none());
+        assert!(compare("123456789012345678901234567890", "123456789012345678901234567891").is_some());
+        assert!(compare("-0", "0").is_none());
+        assert!(compare("1.50", "1.5").is_none());
+    }
 
-        Some(res)
+    #[test]
+    #[cfg(feature = "float-approx")]
+    fn numbers_across_types() {
+        let diff = DiffBuilder::default()
+            .source(json!({"a": 2, "b": u64::MAX, "c": 1}))
+            .target(json!({"a": 2.0, "b": -1, "c": 2}))
+            .build().unwrap()
+            .compare();
+        let Some(Difference::Object { different_entries, .. }) = diff else { panic!("{:?}", diff) };
+        let keys = different_entries.0.iter().map(|(key, _)| key.as_str()).collect::<Vec<_>>();
+        assert_eq!(keys, vec!["b", "c"]);
+
+        let diff = DiffBuilder::default()
+            .numbers_equal_across_types(true)
+            .approx_float_abs_epsilon(1.0)
+            .source(json!({"a": 2, "b": 2.00000, "c": 1}))
+            .target(json!({"a": 2.0, "b": 2, "c": 2}))
+            .build().unwrap()
+            .compare();
+        assert!(diff.is_none(), "diff should be None, but got: {:?}", diff);
     }
-}
 
-impl DerefMut for Path {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+    #[test]
+    #[cfg(feature = "datetime")]
+    fn case_insensitive_strings() {
+        let diff = DiffBuilder::default()
+            .case_insensitive_strings(true)
+            .approx_date_time_eq_duration(Duration::from_secs(1))
+            .source(json!({"country": "KZ", "status": "Straße", "ts": "2023-07-25t15:30:01z"}))
+            .target(json!({"country": "kz", "status": "STRAßE", "ts": "2023-07-25T15:30:00Z"}))
+            .build().unwrap()
+            .compare();
+        assert!(diff.is_none(), "diff should be None, but got: {:?}", diff);
     }
-}
 
-impl From<Vec<PathElement>> for Path {
-    fn from(value: Vec<PathElement>) -> Self {
-        Self(value)
+    #[test]
+    fn string_subdiff() {
+        let compare = |string_subdiff| {
+            DiffBuilder::default()
+                .string_subdiff(string_subdiff)
+                .source(json!({"text": "The quick brown fox"}))
+                .target(json!({"text": "The slow brown fox"}))
+                .build().unwrap()
+                .compare()
+                .unwrap()
+        };
+
+        let value = serde_json::to_value(compare(true)).unwrap();
+        assert_eq!(
+            value["different_entries"]["text"]["value_diff"]["edits"],
+            json!([{"range": {"start": 4, "end": 9}, "replacement": "slow"}])
+        );
+        let value = serde_json::to_value(compare(false)).unwrap();
+        assert!(value["different_entries"]["text"]["value_diff"].get("edits").is_none());
+
+        let reversed = serde_json::to_value(compare(true).reverse()).unwrap();
+        assert_eq!(
+            reversed["different_entries"]["text"]["value_diff"]["edits"],
+            json!([{"range": {"start": 4, "end": 8}, "replacement": "quick"}])
+        );
     }
-}
 
-impl FromStr for Path {
-    type Err = String;
+    #[test]
+    fn compact_type_diff() {
+        let source = json!({"user": {"id": 1, "name": "Joe", "email": "joe@example.com", "tags": ["admin", "ops"]}, "count": [1], "id": 1});
+        let target = json!({"user": "joe@example.com", "count": 1, "id": "1"});
+        let diff = DiffBuilder::default()
+            .compact_type_diff(true)
+            .source(source)
+            .target(target.clone())
+            .build()
+            .unwrap()
+            .compare()
+            .unwrap();
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(Path(parse_element_path(s)?))
+        let value = serde_json::to_value(&diff).unwrap();
+        let entries = &value["different_entries"];
+        assert_eq!(entries["user"]["value_diff"]["source_type"], json!("object"));
+        assert_eq!(
+            entries["user"]["value_diff"]["source_value"],
+            json!(r#"{"email":"joe@example.com","id":1,"name":"Joe","tags":["admin","ops"]}"#[..64].to_string() + "...")
+        );
+        assert_eq!(entries["user"]["value_diff"]["target_value"], json!("joe@example.com"));
+        assert_eq!(entries["count"]["value_diff"]["source_value"], json!([1]));
+        assert_eq!(entries["id"]["value_diff"]["target_value"], json!("1"));
+
+        let mut patched = target;
+        let err = diff.reverse().apply(&mut patched).unwrap_err();
+        assert_eq!(err.to_string(), "cannot apply difference at '/user': target value is compacted");
     }
-}
 
-impl TryFrom<&str> for Path {
-    type Error = String;
+    #[test]
+    fn string_compare_prefix() {
+        let diff = DiffBuilder::default()
+            .string_compare_prefix("assets.[_].url", 14)
+            .string_compare_prefix("hash", 4)
+            .source(json!({"assets": [{"url": "/static/app.js"}], "hash": "a1b2c3", "name": "app"}))
+            .target(json!({"assets": [{"url": "/static/app.js?v=2"}], "hash": "a1b2ff", "name": "app.js"}))
+            .build().unwrap()
+            .compare()
+            .unwrap();
+        assert_eq!(diff.to_text(), "~ name: \"app\" => \"app.js\"");
 
-    fn try_from(s: &str) -> Result<Self, Self::Error> {
-        s.parse()
+        let diff = DiffBuilder::default()
+            .string_compare_prefix("hash", 4)
+            .source(json!({"hash": "a1"}))
+            .target(json!({"hash": "a1b2"}))
+            .build().unwrap()
+            .compare();
+        assert!(diff.is_some());
     }
-}
 
+    #[test]
+    fn normalize_whitespace() {
+        let source = json!({"a": "  Main St.  ", "b": "Main  \t St.\n"});
+        let target = json!({"a": "Main St.", "b": "Main St."});
 
-#[cfg(test)]
-mod tests {
-    use std::time::Duration;
-    use serde_json::json;
-    use crate::{ArrayIndex, DiffBuilder, IgnorePathCondition, Path, PathElement};
+        let diff = DiffBuilder::default()
+            .normalize_whitespace(WhitespaceMode::Trim)
+            .source(source.clone())
+            .target(target.clone())
+            .build().unwrap()
+            .compare();
+        let Some(Difference::Object { different_entries, .. }) = diff else { panic!("{:?}", diff) };
+        let keys = different_entries.0.iter().map(|(key, _)| key.as_str()).collect::<Vec<_>>();
+        assert_eq!(keys, vec!["b"]);
+
+        let diff = DiffBuilder::default()
+            .normalize_whitespace(WhitespaceMode::Collapse)
+            .source(source)
+            .target(target)
+            .build().unwrap()
+            .compare();
+        assert!(diff.is_none(), "diff should be None, but got: {:?}", diff);
+    }
 
     #[test]
-    fn ignore_with_rhai_condition() {
+    #[cfg(feature = "datetime")]
+    fn approx_date_time_eq() {
         let obj1 = json!({
-            "users": [
-                {
-                    "name": "Joe",
-                    "age": 43,
-                },
-                {
-                    "name": "Ana",
-                    "age": 33,
-                    "animals": {
-                        "type": "dog"
-                    }
-                },
-            ]
+            "ts": "2023-07-25T15:30:01Z"
         });
 
         let obj2 = json!({
-            "users": [
-                {
-                    "name": "Joe",
-                    "age": 43,
-                },
-                {
-                    "name": "Ana",
-                    "age": 33,
-                    "animals": {
-                        "type": "cat"
-                    }
-                },
-            ]
+            "ts": "2023-07-25T15:30:00Z"
         });
 
-        let script = r#"
-        let res = target.value_by_path("users.[_].age", curr_path);
-        res == 33
-        "#;
+        let diff = DiffBuilder::default()
+            .approx_date_time_eq_duration(Duration::from_secs(1))
+            .source(obj1).target(obj2).build().unwrap();
 
+        let diff = diff.compare();
+
+        assert_eq!(true, diff.is_none(), "diff should be None, but got: {:?}", diff);
+    }
+
+    #[test]
+    fn canonicalize_objects() {
         let diff = DiffBuilder::default()
-            .source(obj1)
-            .target(obj2)
-            .ignore_path_with_condition("users.[_].animals.type", IgnorePathCondition::Rhai(script.to_string()))
-            .build();
-        let diff = diff.unwrap().compare();
+            .canonicalize_objects(true)
+            .array_key("rows", "key")
+            .source(json!({"rows": [{"key": {"region": "eu", "id": 1}, "n": 1}, {"key": {"region": "us", "id": 1}, "n": 1}]}))
+            .target(json!({"rows": [{"key": {"id": 1, "region": "us"}, "n": 1}, {"key": {"id": 1, "region": "eu"}, "n": 2}]}))
+            .build()
+            .unwrap()
+            .compare()
+            .unwrap();
+        assert_eq!(diff.to_text(), "~ rows.[0].n: 1 => 2");
 
-        assert!(diff.is_none(), "{:?}", diff);
+        let mut canonical = String::new();
+        super::write_canonical(&json!({"b": [{"d": 1, "c": "x\""}], "a": null}), &mut canonical);
+        assert_eq!(canonical, r#"{"a":null,"b":[{"c":"x\"","d":1}]}"#);
     }
 
     #[test]
-    fn ignore_source_missing() {
+    fn keyed_array_elements() {
         let obj1 = json!({
-            "name": "John Doe",
+            "users": [
+                {"id": 1, "name": "Joe"},
+                {"id": 2, "name": "Ana"},
+                {"id": 3, "name": "Bob"},
+            ]
         });
 
         let obj2 = json!({
-            "name": "John Doe",
-            "age": 30
+            "users": [
+                {"id": 4, "name": "Tom"},
+                {"id": 1, "name": "Joe"},
+                {"id": 2, "name": "Anna"},
+            ]
         });
 
         let diff = DiffBuilder::default()
-            .source(obj1)
-            .target(obj2)
-            .ignore_path_with_missing("age", true)
-            .build();
-        let diff = diff.unwrap().compare();
+            .array_key("users", "id")
+            .source(obj1.clone())
+            .target(obj2.clone())
+            .build()
+            .unwrap()
+            .compare()
+            .unwrap();
+
+        let Difference::Object { different_entries, .. } = &diff else { panic!("{:?}", diff) };
+        let EntryDifference::Value { value_diff } = &different_entries.0[0].1 else { panic!("{:?}", diff) };
+        let Difference::Array(ArrayDifference::Keyed { different_pairs, missing_elements, extra_elements }) = value_diff else {
+            panic!("{:?}", diff)
+        };
+        assert_eq!(different_pairs.len(), 1);
+        assert_eq!(different_pairs[0].key, json!(2));
+        assert_eq!((different_pairs[0].source_index, different_pairs[0].target_index), (1, 2));
+        assert_eq!(missing_elements.0, vec![(0, json!({"id": 4, "name": "Tom"}))]);
+        assert_eq!(extra_elements.0, vec![(2, json!({"id": 3, "name": "Bob"}))]);
 
+        let mut patched = obj1;
+        diff.apply(&mut patched).unwrap();
+        let diff = DiffBuilder::default()
+            .array_key("users", "id")
+            .source(patched)
+            .target(obj2)
+            .build()
+            .unwrap()
+            .compare();
         assert!(diff.is_none(), "{:?}", diff);
     }
 
     #[test]
-    fn equal_objects() {
-        let obj1 = json!({
-            "string": "b",
-            "int": 1,
-            "float": 1.0,
-            "bool": true,
-            "int_array": [1, 2, 3],
-            "float_array": [1.0, 2.0, 3.0],
-            "bool_array": [true, false, false],
-            "string_array": ["foo", "bar"],
-            "empty_array": [],
-            "null": null,
-            "object": {
-                "string": "c",
-                "int": 1,
-                "float": 1.0,
-                "bool": true,
-                "array": [1, 2, 3],
-                "null": null,
-                "object": {
-                    "string": "d",
-                }
-            },
-        });
+    fn lcs_aligned_array_elements() {
+        let obj1 = json!({"items": ["a", "b", {"c": 1}, "d", "e"]});
+        let obj2 = json!({"items": ["x", "a", "b", {"c": 2}, "e", "f"]});
 
-        let obj2 = json!({
-            "string": "b",
-            "int": 1,
-            "float": 1.0,
-            "bool": true,
-            "int_array": [1, 2, 3],
-            "float_array": [1.0, 2.0, 3.0],
-            "bool_array": [true, false, false],
-            "string_array": ["foo", "bar"],
-            "empty_array": [],
-            "null": null,
-            "object": {
-                "string": "c",
-                "int": 1,
-                "float": 1.0,
-                "bool": true,
-                "array": [1, 2, 3],
-                "null": null,
-                "object": {
-                    "string": "d",
-                }
-            },
-        });
+        let diff = DiffBuilder::default()
+            .array_alignment(ArrayAlignment::Lcs)
+            .source(obj1.clone())
+            .target(obj2.clone())
+            .build()
+            .unwrap()
+            .compare()
+            .unwrap();
 
-        let diff = DiffBuilder::default().source(obj1).target(obj2).build().unwrap();
-        let diff = diff.compare();
-        assert_eq!(true, diff.is_none(), "diff should be None, but got: {:?}", diff);
-    }
+        let Difference::Object { different_entries, .. } = &diff else { panic!("{:?}", diff) };
+        let EntryDifference::Value { value_diff } = &different_entries.0[0].1 else { panic!("{:?}", diff) };
+        let Difference::Array(ArrayDifference::Aligned { different_pairs, removed_elements, inserted_elements, .. }) = value_diff else {
+            panic!("{:?}", diff)
+        };
+        assert_eq!(different_pairs.0.iter().map(|(i, _)| *i).collect::<Vec<_>>(), vec![2]);
+        assert_eq!(removed_elements.0, vec![(3, json!("d"))]);
+        assert_eq!(inserted_elements.0, vec![(0, json!("x")), (5, json!("f"))]);
 
+        let mut patched = obj1;
+        diff.apply(&mut patched).unwrap();
+        assert_eq!(patched, obj2);
+    }
 
     #[test]
-    fn ignore_fields() {
-        let user_1 = json!({
-            "user": "John",
-            "address": {
-                "city": "Astana",
-                "zip": 123,
-            },
-            "animals": ["dog", "cat"],
-            "object_array": [{"a": "b", "c": "d"}],
-            "optional_array": [],
-            "target_missing_value": 1,
-        });
+    fn array_output_ops() {
+        let compare = |alignment, source: &serde_json::Value, target: &serde_json::Value| {
+            DiffBuilder::default()
+                .array_alignment(alignment)
+                .array_output(ArrayOutput::Ops)
+                .source(source.clone())
+                .target(target.clone())
+                .build()
+                .unwrap()
+                .compare()
+        };
 
-        let user_2 = json!({
-            "user": "Joe",
-            "address": {
-                "city": "Boston",
-                "zip": 312,
-            },
-            "animals": ["dog", "cat"],
-            "object_array": [{"a": "3", "c": "d"}],
-            "optional_array": null,
-        });
+        let source = json!(["a", "b", {"c": 1}, "d", "e"]);
+        let target = json!(["x", "a", "b", {"c": 2}, "e", "f"]);
+        let diff = compare(ArrayAlignment::Lcs, &source, &target).unwrap();
+        assert_eq!(serde_json::to_value(&diff).unwrap()["ops"], json!([
+            {"op": "insert", "index": 0, "value": "x"},
+            {"op": "keep", "count": 2},
+            {"op": "replace", "index": 2, "difference": diff_at(&diff, 2)},
+            {"op": "delete", "index": 3, "value": "d"},
+            {"op": "keep", "count": 1},
+            {"op": "insert", "index": 5, "value": "f"},
+        ]));
+        let paths: Vec<_> = diff.flatten().into_iter().map(|(path, _)| path.to_string()).collect();
+        assert_eq!(paths, ["[0]", "[2].c", "[3]", "[5]"]);
 
-        let diff = DiffBuilder::default()
-            .ignore_path("user")
-            .ignore_path("address.city")
-            .ignore_path("address.zip")
-            .ignore_path("object_array.[_].a")
-            .ignore_path_with_missing("target_missing_value", true)
-            .equate_empty_arrays(true)
-            .source(user_1)
-            .target(user_2)
-            .build()
-            .unwrap();
+        let source = json!([1, 2, 3, 4]);
+        let target = json!([1, 5, 3]);
+        let diff = compare(ArrayAlignment::Index, &source, &target).unwrap();
+        assert_eq!(serde_json::to_value(&diff).unwrap()["ops"], json!([
+            {"op": "keep", "count": 1},
+            {"op": "replace", "index": 1, "difference": diff_at(&diff, 1)},
+            {"op": "keep", "count": 1},
+            {"op": "delete", "index": 3, "value": 4},
+        ]));
 
-        let diff = diff.compare();
+        for (alignment, source, target) in [
+            (ArrayAlignment::Index, json!([1, 2, 3, 4]), json!([1, 5, 3])),
+            (ArrayAlignment::Index, json!([1]), json!([2, 3, [4]])),
+            (ArrayAlignment::Lcs, json!(["a", "b", {"c": 1}, "d", "e"]), json!(["x", "a", "b", {"c": 2}, "e", "f"])),
+            (ArrayAlignment::Lcs, json!([[1, 2], "y", "z"]), json!(["z", [1, 3, 4]])),
+        ] {
+            let diff = compare(alignment, &source, &target).unwrap();
+            let mut patched = source.clone();
+            diff.apply(&mut patched).unwrap();
+            assert_eq!(patched, target);
+            let mut reverted = target.clone();
+            diff.reverse().apply(&mut reverted).unwrap();
+            assert_eq!(reverted, source);
+        }
 
-        assert_eq!(true, diff.is_none(), "diff should be None, but got: {:?}", diff);
+        assert!(compare(ArrayAlignment::Lcs, &json!([1, 2]), &json!([1, 2])).is_none());
+        assert!(compare(ArrayAlignment::Index, &json!([1, 2]), &json!([1, 2])).is_none());
     }
 
-    #[test]
-    fn approx_float_eq() {
-        let obj1 = json!({
-            "float": 1.34
-        });
+    /// Returns the serialized difference of the `idx`-th replaced element of [`ArrayDifference::Ops`].
+    fn diff_at(diff: &Difference, idx: usize) -> serde_json::Value {
+        let Difference::Array(ArrayDifference::Ops { ops }) = diff else { panic!("{:?}", diff) };
+        ops.iter()
+            .find_map(|op| match op {
+                ArrayOp::Replace { index, difference } if *index == idx => Some(serde_json::to_value(difference).unwrap()),
+                _ => None,
+            })
+            .unwrap()
+    }
 
-        let obj2 = json!({
-            "float": 1.341
-        });
+    #[test]
+    fn moved_array_elements() {
+        let source = json!(["a", "b", "c", "d", "e"]);
+        let target = json!(["d", "a", "b", "x", "c", "e"]);
 
         let diff = DiffBuilder::default()
-            .approx_float_eq_epsilon(0.001)
-            .source(obj1).target(obj2).build().unwrap();
+            .array_alignment(ArrayAlignment::Lcs)
+            .detect_moved_elements(true)
+            .source(source.clone())
+            .target(target.clone())
+            .build()
+            .unwrap()
+            .compare()
+            .unwrap();
 
-        let diff = diff.compare();
+        let Difference::Array(ArrayDifference::Aligned { different_pairs, removed_elements, inserted_elements, moved_elements }) = &diff else {
+            panic!("{:?}", diff)
+        };
+        assert!(different_pairs.0.is_empty());
+        assert!(removed_elements.0.is_empty());
+        assert_eq!(inserted_elements.0, vec![(3, json!("x"))]);
+        assert_eq!(moved_elements.len(), 1);
+        assert_eq!((moved_elements[0].source_index, moved_elements[0].target_index), (3, 0));
+        assert_eq!(diff.to_text(), "> [3] => [0]\n+ [3]: \"x\"");
 
-        assert_eq!(true, diff.is_none(), "diff should be None, but got: {:?}", diff);
+        let mut patched = source;
+        diff.apply(&mut patched).unwrap();
+        assert_eq!(patched, target);
     }
 
     #[test]
-    fn approx_date_time_eq() {
-        let obj1 = json!({
-            "ts": "2023-07-25T15:30:01Z"
-        });
-
-        let obj2 = json!({
-            "ts": "2023-07-25T15:30:00Z"
-        });
+    #[cfg(feature = "datetime")]
+    fn approx_date_time_eq_with_formats() {
+        let diff = DiffBuilder::default()
+            .approx_date_time_eq_duration(Duration::from_secs(1))
+            .date_time_formats(vec![
+                "%Y-%m-%d %H:%M:%S".to_string(),
+                "%s".to_string(),
+                "%a, %d %b %Y %H:%M:%S %z".to_string(),
+            ])
+            .source(json!({"a": "2023-07-25 15:30:01", "b": "1690299001", "c": "Tue, 25 Jul 2023 15:30:01 +0000"}))
+            .target(json!({"a": "2023-07-25 15:30:00", "b": "2023-07-25T15:30:00Z", "c": "Tue, 25 Jul 2023 17:30:00 +0200"}))
+            .build().unwrap()
+            .compare();
+        assert!(diff.is_none(), "diff should be None, but got: {:?}", diff);
 
         let diff = DiffBuilder::default()
             .approx_date_time_eq_duration(Duration::from_secs(1))
-            .source(obj1).target(obj2).build().unwrap();
+            .source(json!({"a": "2023-07-25 15:30:01"}))
+            .target(json!({"a": "2023-07-25 15:30:00"}))
+            .build().unwrap()
+            .compare();
+        assert!(diff.is_some());
+    }
 
-        let diff = diff.compare();
+    #[test]
+    #[cfg(feature = "datetime")]
+    fn approx_date_eq() {
+        let diff = DiffBuilder::default()
+            .approx_date_eq_days(1)
+            .source(json!({"a": "2023-07-25", "b": "2023-07-25"}))
+            .target(json!({"a": "2023-07-26", "b": "2023-07-27"}))
+            .build().unwrap()
+            .compare();
+        let Some(Difference::Object { different_entries, .. }) = diff else { panic!("{:?}", diff) };
+        let keys = different_entries.0.iter().map(|(key, _)| key.as_str()).collect::<Vec<_>>();
+        assert_eq!(keys, vec!["b"]);
+    }
 
-        assert_eq!(true, diff.is_none(), "diff should be None, but got: {:?}", diff);
+    #[test]
+    fn path_display() {
+        for path in ["a", "users.[1].age", "[_].a.[2]", "'[_]'.a", "a.'.'.b", "'a.b'.'c]'", r#"'it\'s'.'a\\b'.'say "hi"'"#, r"~'^\d+\'$'"] {
+            let parsed: Path = path.parse().unwrap();
+            assert_eq!(parsed.to_string(), path);
+        }
+
+        let path: Path = vec![
+            PathElement::Key("address".to_string()),
+            PathElement::Key("zip.code".to_string()),
+        ].into();
+        assert_eq!(path.to_string().parse::<Path>().unwrap(), path);
     }
 
     #[test]
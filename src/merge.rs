@@ -0,0 +1,233 @@
+use crate::{ArrayDifference, Difference, EntryDifference, Map};
+
+impl Difference {
+    /// Returns the union of both differences, e.g. to combine the differences of several
+    /// partitions of a dataset that were compared in parallel.
+    ///
+    /// Object entries, array pairs and array elements of both differences are united, entries of `self` come first
+    /// followed by the ones only `other` has. If both of them have a value difference for the same key
+    /// or index, the two are merged recursively. In any other conflict, e.g. two scalar differences
+    /// or arrays compared in different ways, the difference of `self` is preferred. [`ArrayDifference::Ops`]
+    /// aren't merged either, as each of them is an edit script of the whole array.
+    /// Both differences count the same truncated entries or elements, so the larger `truncated_count` is kept.
+    pub fn merge(&self, other: &Difference) -> Difference {
+        match (self, other) {
+            (
+                Difference::Object { different_entries, truncated_count, key_order },
+                Difference::Object { different_entries: other_entries, truncated_count: other_truncated_count, key_order: other_key_order },
+            ) => {
+                let mut entries = different_entries.0.clone();
+                for (key, other_entry) in &other_entries.0 {
+                    match entries.iter_mut().find(|(entry_key, _)| entry_key == key) {
+                        Some((_, EntryDifference::Value { value_diff })) => {
+                            if let EntryDifference::Value { value_diff: other_diff } = other_entry {
+                                *value_diff = value_diff.merge(other_diff);
+                            }
+                        }
+                        Some(_) => {}
+                        None => entries.push((key.clone(), other_entry.clone())),
+                    }
+                }
+
+                Difference::Object {
+                    different_entries: Map(entries),
+                    truncated_count: *truncated_count.max(other_truncated_count),
+                    key_order: key_order.clone().or_else(|| other_key_order.clone()),
+                }
+            }
+            (Difference::Array(array), Difference::Array(other_array)) => Difference::Array(merge_arrays(array, other_array)),
+            (difference, _) => difference.clone(),
+        }
+    }
+}
+
+fn merge_arrays(array: &ArrayDifference, other: &ArrayDifference) -> ArrayDifference {
+    match (array, other) {
+        (ArrayDifference::PairsOnly { different_pairs }, ArrayDifference::PairsOnly { different_pairs: other_pairs }) => {
+            ArrayDifference::PairsOnly { different_pairs: merge_pairs(different_pairs, other_pairs) }
+        }
+        (
+            ArrayDifference::Shorter { different_pairs, source_length, missing_elements, truncated_count },
+            ArrayDifference::Shorter {
+                different_pairs: other_pairs,
+                truncated_count: other_truncated_count,
+                ..
+            },
+        ) => ArrayDifference::Shorter {
+            different_pairs: merge_optional_pairs(different_pairs, other_pairs),
+            source_length: *source_length,
+            missing_elements: missing_elements.clone(),
+            truncated_count: *truncated_count.max(other_truncated_count),
+        },
+        (
+            ArrayDifference::Longer { different_pairs, target_length, extra_length, extra_elements, truncated_count },
+            ArrayDifference::Longer {
+                different_pairs: other_pairs,
+                truncated_count: other_truncated_count,
+                ..
+            },
+        ) => ArrayDifference::Longer {
+            different_pairs: merge_optional_pairs(different_pairs, other_pairs),
+            target_length: *target_length,
+            extra_length: *extra_length,
+            extra_elements: extra_elements.clone(),
+            truncated_count: *truncated_count.max(other_truncated_count),
+        },
+        (
+            ArrayDifference::Keyed { different_pairs, missing_elements, extra_elements },
+            ArrayDifference::Keyed {
+                different_pairs: other_pairs,
+                missing_elements: other_missing_elements,
+                extra_elements: other_extra_elements,
+            },
+        ) => {
+            let mut pairs = different_pairs.clone();
+            for other_pair in other_pairs {
+                match pairs.iter_mut().find(|pair| pair.source_index == other_pair.source_index) {
+                    Some(pair) => pair.difference = pair.difference.merge(&other_pair.difference),
+                    None => pairs.push(other_pair.clone()),
+                }
+            }
+            pairs.sort_by_key(|pair| pair.source_index);
+
+            ArrayDifference::Keyed {
+                different_pairs: pairs,
+                missing_elements: merge_elements(missing_elements, other_missing_elements),
+                extra_elements: merge_elements(extra_elements, other_extra_elements),
+            }
+        }
+        (
+            ArrayDifference::Aligned { different_pairs, removed_elements, inserted_elements, moved_elements },
+            ArrayDifference::Aligned {
+                different_pairs: other_pairs,
+                removed_elements: other_removed_elements,
+                inserted_elements: other_inserted_elements,
+                moved_elements: other_moved_elements,
+            },
+        ) => {
+            let mut moved = moved_elements.clone();
+            for other_moved in other_moved_elements {
+                if !moved.iter().any(|element| element.source_index == other_moved.source_index) {
+                    moved.push(other_moved.clone());
+                }
+            }
+            moved.sort_by_key(|element| element.source_index);
+
+            ArrayDifference::Aligned {
+                different_pairs: merge_pairs(different_pairs, other_pairs),
+                removed_elements: merge_elements(removed_elements, other_removed_elements),
+                inserted_elements: merge_elements(inserted_elements, other_inserted_elements),
+                moved_elements: moved,
+            }
+        }
+        (array, _) => array.clone(),
+    }
+}
+
+fn merge_optional_pairs(
+    pairs: &Option<Map<usize, Difference>>,
+    other: &Option<Map<usize, Difference>>,
+) -> Option<Map<usize, Difference>> {
+    match (pairs, other) {
+        (Some(pairs), Some(other)) => Some(merge_pairs(pairs, other)),
+        (pairs, other) => pairs.as_ref().or(other.as_ref()).cloned(),
+    }
+}
+
+/// Unites the pairs of both maps keeping them sorted by their index.
+fn merge_pairs(pairs: &Map<usize, Difference>, other: &Map<usize, Difference>) -> Map<usize, Difference> {
+    let mut merged = pairs.0.clone();
+    for (idx, other_diff) in &other.0 {
+        match merged.iter_mut().find(|(pair_idx, _)| pair_idx == idx) {
+            Some((_, difference)) => *difference = difference.merge(other_diff),
+            None => merged.push((*idx, other_diff.clone())),
+        }
+    }
+    merged.sort_by_key(|(idx, _)| *idx);
+
+    Map(merged)
+}
+
+/// Unites the elements of both maps keeping them sorted by their index,
+/// `elements` wins if both of them have an element at the same index.
+fn merge_elements(
+    elements: &Map<usize, serde_json::Value>,
+    other: &Map<usize, serde_json::Value>,
+) -> Map<usize, serde_json::Value> {
+    let mut merged = elements.0.clone();
+    for (idx, element) in &other.0 {
+        if !merged.iter().any(|(element_idx, _)| element_idx == idx) {
+            merged.push((*idx, element.clone()));
+        }
+    }
+    merged.sort_by_key(|(idx, _)| *idx);
+
+    Map(merged)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use crate::{ArrayAlignment, DiffBuilder};
+
+    #[test]
+    fn merge() {
+        let compare = |source, target| {
+            DiffBuilder::default().source(source).target(target).build().unwrap().compare().unwrap()
+        };
+        let source = json!({"a": 1, "b": {"x": 1, "y": 1}, "items": [1, 2, 3], "c": "s"});
+
+        let first = compare(source.clone(), json!({"a": 2, "b": {"x": 2, "y": 1}, "items": [0, 2, 3], "c": "s"}));
+        let second = compare(source, json!({"a": 3, "b": {"x": 1, "y": 2}, "items": [1, 2, 0], "c": "t"}));
+
+        let merged = first.merge(&second);
+        assert_eq!(
+            merged.to_text(),
+            [
+                "~ a: 1 => 2",
+                "~ b.x: 1 => 2",
+                "~ b.y: 1 => 2",
+                "~ items.[0]: 1 => 0",
+                "~ items.[2]: 3 => 0",
+                "~ c: \"s\" => \"t\"",
+            ].join("\n")
+        );
+
+        let scalar = compare(json!(1), json!(2));
+        assert_eq!(scalar.merge(&first).to_text(), "~ (root): 1 => 2");
+
+        let compare = |builder: &mut DiffBuilder, target| {
+            let source = json!({"items": [{"id": 1, "v": 1}, {"id": 2, "v": 1}, {"id": 3}]});
+            builder.source(source).target(target).build().unwrap().compare().unwrap()
+        };
+        let mut keyed = DiffBuilder::default();
+        keyed.array_key("items", "id");
+        let first = compare(&mut keyed, json!({"items": [{"id": 1, "v": 2}, {"id": 2, "v": 1}, {"id": 3}, {"id": 4}]}));
+        let second = compare(&mut keyed, json!({"items": [{"id": 1, "v": 1}, {"id": 2, "v": 2}]}));
+        assert_eq!(first.merge(&second).to_text(), [
+            "~ items.[0].v: 1 => 2",
+            "~ items.[1].v: 1 => 2",
+            "+ items.[3]: {\"id\":4}",
+            "- items.[2]",
+        ].join("\n"));
+
+        let mut aligned = DiffBuilder::default();
+        aligned.array_alignment(ArrayAlignment::Lcs);
+        let inserted = json!({"items": [{"id": 0}, {"id": 1, "v": 1}, {"id": 2, "v": 1}, {"id": 3}]});
+        let first = compare(&mut aligned, inserted);
+        let second = compare(&mut aligned, json!({"items": [{"id": 1, "v": 1}, {"id": 2, "v": 1}]}));
+        assert_eq!(first.merge(&second).to_text(), ["- items.[2]", "+ items.[0]: {\"id\":0}"].join("\n"));
+
+        let truncated = DiffBuilder::default()
+            .max_entry_differences(1)
+            .max_reported_array_elements(1)
+            .source(json!({"a": 1, "b": 1, "c": [1, 2, 3]}))
+            .target(json!({"a": 2, "b": 2, "c": [1]}))
+            .build()
+            .unwrap()
+            .compare()
+            .unwrap();
+        assert_eq!(truncated.merge(&truncated), truncated);
+        assert_eq!(first.merge(&first), first);
+    }
+}
@@ -0,0 +1,85 @@
+use crate::{ApplyError, Difference, EntryDifference};
+
+impl Difference {
+    /// Converts the difference to an [RFC 7386](https://datatracker.ietf.org/doc/html/rfc7386)
+    /// JSON Merge Patch, which transforms `source` into `target` when applied.
+    ///
+    /// Object entries that are missing in `source` or have a different value become the target value,
+    /// and extra entries become `null`. Nested objects are patched recursively, truncated entries are skipped.
+    ///
+    /// Arrays are replaced as a whole as the spec requires, so the patch has to carry every element
+    /// of the target array. A difference only keeps the elements that differ, e.g.
+    /// [`ArrayDifference::PairsOnly`](crate::ArrayDifference::PairsOnly) has no equal elements,
+    /// so it takes `source` to reconstruct the target arrays with [`Difference::apply`].
+    /// Its [`ApplyError`] is returned if `source` doesn't match the difference.
+    ///
+    /// <div class="warning">
+    ///
+    /// **NOTE**: merge patches can't express setting a value to `null`,
+    /// such entries will be removed when the patch is applied.
+    ///
+    /// </div>
+    pub fn to_merge_patch(&self, source: &serde_json::Value) -> Result<serde_json::Value, ApplyError> {
+        let Difference::Object { different_entries, .. } = self else {
+            let mut target = source.clone();
+            self.apply(&mut target)?;
+            return Ok(target);
+        };
+
+        let patch = different_entries
+            .0
+            .iter()
+            .map(|(key, entry)| {
+                let value = match entry {
+                    EntryDifference::Missing { value } => value.clone(),
+                    EntryDifference::Extra { .. } => serde_json::Value::Null,
+                    EntryDifference::Value { value_diff } => {
+                        value_diff.to_merge_patch(source.get(key).unwrap_or(&serde_json::Value::Null))?
+                    }
+                };
+                Ok((key.clone(), value))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(serde_json::Value::Object(patch))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use crate::DiffBuilder;
+
+    fn merge_patch(source: serde_json::Value, target: serde_json::Value) -> serde_json::Value {
+        let diff = DiffBuilder::default().source(source.clone()).target(target).build().unwrap();
+        diff.compare().map(|diff| diff.to_merge_patch(&source).unwrap()).unwrap_or(json!({}))
+    }
+
+    #[test]
+    fn object_entries() {
+        assert_eq!(
+            merge_patch(
+                json!({"name": "Joe", "age": 31, "address": {"city": "Astana", "zip": 1}}),
+                json!({"age": 33, "email": "joe@example.com", "address": {"city": "Boston", "zip": 1}}),
+            ),
+            json!({"name": null, "age": 33, "email": "joe@example.com", "address": {"city": "Boston"}})
+        );
+    }
+
+    #[test]
+    fn arrays_are_replaced() {
+        assert_eq!(
+            merge_patch(
+                json!({"users": [{"name": "Joe", "age": 1}, {"name": "Ana", "age": 2}], "tags": [1, 2, 3]}),
+                json!({"users": [{"name": "Joe", "age": 1}, {"name": "Ana", "age": 3}], "tags": [1]}),
+            ),
+            json!({"users": [{"name": "Joe", "age": 1}, {"name": "Ana", "age": 3}], "tags": [1]})
+        );
+    }
+
+    #[test]
+    fn non_object_target() {
+        assert_eq!(merge_patch(json!({"a": 1}), json!("a")), json!("a"));
+        assert_eq!(merge_patch(json!([1, 2]), json!([1, 2, 3])), json!([1, 2, 3]));
+    }
+}
@@ -0,0 +1,131 @@
+use std::fmt;
+use std::io::{BufRead, BufReader, Read};
+use crate::{CompareError, Diff, DiffBuilder, DiffConfig, Difference};
+
+/// An error returned by [`DiffConfig::compare_ndjson`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum NdjsonError {
+    /// A line of the `source` or `target` input isn't valid JSON or can't be read,
+    /// `line` is counted from 1.
+    Parse {
+        field: &'static str,
+        line: usize,
+        error: String,
+    },
+    /// The records at index `record` can't be compared, see [`DiffConfig::try_compare`].
+    Compare {
+        record: usize,
+        error: CompareError,
+    },
+}
+
+impl fmt::Display for NdjsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NdjsonError::Parse { field, line, error } => {
+                write!(f, "{} line {} can't be parsed: {}", field, line, error)
+            }
+            NdjsonError::Compare { record, error } => write!(f, "records {} can't be compared: {}", record, error),
+        }
+    }
+}
+
+impl std::error::Error for NdjsonError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            NdjsonError::Parse { .. } => None,
+            NdjsonError::Compare { error, .. } => Some(error),
+        }
+    }
+}
+
+impl Diff {
+    /// Compares two [NDJSON](https://github.com/ndjson/ndjson-spec) inputs, e.g. event logs, record by record
+    /// with the default settings, see [`DiffConfig::compare_ndjson`].
+    ///
+    /// ```rust
+    /// let source = "{\"id\": 1, \"level\": \"info\"}\n{\"id\": 2, \"level\": \"info\"}\n";
+    /// let target = "{\"id\": 1, \"level\": \"info\"}\n{\"id\": 2, \"level\": \"warn\"}\n";
+    ///
+    /// let records = sjdiff::Diff::compare_ndjson(source.as_bytes(), target.as_bytes()).unwrap();
+    /// assert!(records[0].1.is_none());
+    /// assert_eq!(records[1].1.as_ref().unwrap().to_text(), "~ level: \"info\" => \"warn\"");
+    /// ```
+    pub fn compare_ndjson(
+        source: impl Read,
+        target: impl Read,
+    ) -> Result<Vec<(usize, Option<Difference>)>, NdjsonError> {
+        let config = DiffBuilder::default().build_config().expect("default settings are valid");
+        config.compare_ndjson(source, target)
+    }
+}
+
+impl DiffConfig {
+    /// Parses every non-empty line of `source` and `target` as a JSON record and compares the records
+    /// at the same position using the settings. The index of every record is returned along with
+    /// its difference, or `None` if the records are equal. If one input has more records,
+    /// the extra ones are compared with `null`. Readers are buffered internally.
+    ///
+    /// Returns [`NdjsonError::Parse`] with the line number if a line isn't valid JSON or can't be read,
+    /// or [`NdjsonError::Compare`] with the record index if the records can't be compared.
+    pub fn compare_ndjson(
+        &self,
+        source: impl Read,
+        target: impl Read,
+    ) -> Result<Vec<(usize, Option<Difference>)>, NdjsonError> {
+        let source = parse_lines("source", source)?;
+        let target = parse_lines("target", target)?;
+
+        let null = serde_json::Value::Null;
+        (0..source.len().max(target.len()))
+            .map(|idx| {
+                let difference = self
+                    .try_compare(source.get(idx).unwrap_or(&null), target.get(idx).unwrap_or(&null))
+                    .map_err(|error| NdjsonError::Compare { record: idx, error })?;
+                Ok((idx, difference))
+            })
+            .collect()
+    }
+}
+
+fn parse_lines(field: &'static str, reader: impl Read) -> Result<Vec<serde_json::Value>, NdjsonError> {
+    let mut records = vec![];
+    for (idx, line) in BufReader::new(reader).lines().enumerate() {
+        let error = |error: String| NdjsonError::Parse { field, line: idx + 1, error };
+        let line = line.map_err(|err| error(err.to_string()))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        records.push(serde_json::from_str(&line).map_err(|err| error(err.to_string()))?);
+    }
+
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{CompareError, Diff, DiffBuilder, NdjsonError};
+
+    #[test]
+    fn compare_ndjson() {
+        let source = "{\"id\": 1, \"at\": 10}\n\n{\"id\": 2, \"at\": 11}\r\n{\"id\": 3, \"at\": 12}";
+        let target = "{\"id\": 1, \"at\": 20}\n{\"id\": 4, \"at\": 21}\n";
+        let config = DiffBuilder::default().ignore_path("at").build_config().unwrap();
+
+        let records = config.compare_ndjson(source.as_bytes(), target.as_bytes()).unwrap();
+        let texts: Vec<_> = records.iter().map(|(idx, diff)| (*idx, diff.as_ref().map(|diff| diff.to_text()))).collect();
+        assert_eq!(texts, vec![
+            (0, None),
+            (1, Some("~ id: 2 => 4".to_string())),
+            (2, Some(r#"~ (root): {"at":12,"id":3} => null"#.to_string())),
+        ]);
+
+        let err = Diff::compare_ndjson("{}\n".as_bytes(), "{}\n{\"a\":\n".as_bytes()).unwrap_err();
+        assert!(matches!(err, NdjsonError::Parse { field: "target", line: 2, .. }), "{:?}", err);
+
+        let config = DiffBuilder::default().max_depth(1).build_config().unwrap();
+        let err = config.compare_ndjson("[1]\n[[1]]\n".as_bytes(), "[1]\n[[2]]\n".as_bytes()).unwrap_err();
+        let NdjsonError::Compare { record: 1, error } = err else { panic!("{:?}", err) };
+        assert!(matches!(error, CompareError::MaxDepthExceeded { .. }), "{:?}", error);
+    }
+}
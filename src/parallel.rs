@@ -0,0 +1,78 @@
+use rayon::prelude::*;
+use crate::{ArrayIndex, CompareError, Diff, Difference, Path, PathElement};
+
+impl Diff {
+    /// Compares the elements of the arrays that have the same index in parallel, see [`Diff::parallel`].
+    /// Every element gets its own copy of `curr_path`.
+    pub(crate) fn parallel_pairs(
+        &self,
+        curr_path: &Path,
+        source: &[serde_json::Value],
+        target: &[serde_json::Value],
+    ) -> Result<Vec<(usize, Difference)>, CompareError> {
+        let pairs = source.par_iter()
+            .zip(target.par_iter())
+            .enumerate()
+            .map(|(idx, (source, target))| {
+                let mut path = curr_path.clone();
+                path.push(PathElement::ArrayIndex(ArrayIndex::Index(idx)));
+                Ok(self.values(&mut path, source, target)?.map(|difference| (idx, difference)))
+            })
+            .collect::<Result<Vec<_>, CompareError>>()?;
+
+        Ok(pairs.into_iter().flatten().collect())
+    }
+
+    /// Does the same as [`Diff::equal_matrix`], but compares the rows in parallel.
+    pub(crate) fn parallel_equal_matrix(
+        &self,
+        curr_path: &Path,
+        source: &[serde_json::Value],
+        target: &[serde_json::Value],
+    ) -> Result<Vec<bool>, CompareError> {
+        let rows = source.par_iter()
+            .enumerate()
+            .map(|(i, source)| {
+                let mut path = curr_path.clone();
+                path.push(PathElement::ArrayIndex(ArrayIndex::Index(i)));
+                target.iter()
+                    .map(|target| Ok(self.values(&mut path, source, target)?.is_none()))
+                    .collect::<Result<Vec<_>, CompareError>>()
+            })
+            .collect::<Result<Vec<_>, CompareError>>()?;
+
+        Ok(rows.concat())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use crate::{ArrayAlignment, DiffBuilder};
+
+    #[test]
+    fn parallel() {
+        let source: Vec<_> = (0..200).map(|i| json!({"id": i, "tags": [i, i + 1], "name": format!("user {}", i)})).collect();
+        let target: Vec<_> = (0..210)
+            .filter(|i| i % 7 != 0)
+            .map(|i| json!({"id": i, "tags": [i, i + (i % 3)], "name": format!("user {}", i)}))
+            .collect();
+
+        for array_alignment in [ArrayAlignment::Index, ArrayAlignment::Lcs] {
+            let compare = |parallel| {
+                DiffBuilder::default()
+                    .parallel(parallel)
+                    .array_alignment(array_alignment)
+                    .source(json!({"users": source}))
+                    .target(json!({"users": target}))
+                    .build()
+                    .unwrap()
+                    .compare()
+                    .unwrap()
+                    .to_text()
+            };
+
+            assert_eq!(compare(true), compare(false));
+        }
+    }
+}
@@ -0,0 +1,66 @@
+//! Parsing of string paths like `users.[_].age`, e.g. to validate the paths users type
+//! before passing them to [`DiffBuilder::ignore_path`](crate::DiffBuilder::ignore_path).
+//!
+//! ```rust
+//! use sjdiff::path;
+//!
+//! let parsed = path::parse("users.[_].age").unwrap();
+//! assert_eq!(parsed.to_string(), "users.[_].age");
+//!
+//! let err = path::parse("users.[x]").unwrap_err();
+//! assert_eq!((err.message.as_str(), err.offset), ("Invalid array index: x", 7));
+//! ```
+
+use std::fmt;
+use crate::element_path_parser::parse_element_path;
+
+pub use crate::{ArrayIndex, Path, PathElement};
+
+/// An error returned by [`parse`] when the string isn't a valid path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathParseError {
+    /// Human-readable reason of the failure, e.g. `Unclosed bracket`.
+    pub message: String,
+    /// Index of the offending character, e.g. the opening bracket or quote if it's never closed.
+    pub offset: usize,
+}
+
+impl fmt::Display for PathParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at offset {}", self.message, self.offset)
+    }
+}
+
+impl std::error::Error for PathParseError {}
+
+/// Parses `s` into a [`Path`], see [`DiffBuilder::ignore_path`](crate::DiffBuilder::ignore_path)
+/// for the syntax.
+pub fn parse(s: &str) -> Result<Path, PathParseError> {
+    parse_element_path(s).map(Path::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_errors() {
+        let error = |s: &str| {
+            let err = parse(s).unwrap_err();
+            (err.message, err.offset)
+        };
+
+        assert_eq!(error(""), ("Empty path is not allowed".to_string(), 0));
+        assert_eq!(error(".a"), ("Path cannot start with a dot".to_string(), 0));
+        assert_eq!(error("a.b]"), ("Unexpected closing bracket".to_string(), 3));
+        assert_eq!(error("a.[1x]"), ("Invalid array index: 1x".to_string(), 3));
+        assert_eq!(error("ключ.[x]"), ("Invalid array index: x".to_string(), 6));
+        assert_eq!(error("a.'b"), ("Unclosed quote".to_string(), 2));
+        assert_eq!(error("a.~'b\\"), ("Unclosed quote".to_string(), 2));
+        assert_eq!(error("users.[_.age"), ("Unexpected '.' in brackets".to_string(), 8));
+        assert_eq!(error("a.[[0]]"), ("Unexpected '[' in brackets".to_string(), 3));
+        assert_eq!(error(r#"a["b"c]"#), ("Unexpected characters after quoted key: c".to_string(), 6));
+        assert_eq!(parse("a.[").unwrap_err().to_string(), "Unclosed bracket at offset 2");
+        assert_eq!(parse("a.[0]").unwrap(), crate::path!["a", 0]);
+    }
+}
@@ -0,0 +1,192 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::io::Read;
+use serde::de::{self, DeserializeSeed, MapAccess, SeqAccess, Visitor};
+use crate::json_patch::json_pointer;
+use crate::{ArrayIndex, Diff, DiffBuilder, DiffBuilderError, Path, PathElement};
+
+impl Diff {
+    /// Parses `source` and `target` as JSON from the readers, e.g. files, and returns a [`DiffBuilder`]
+    /// with both of them set, so only the settings are left to configure. Readers aren't buffered,
+    /// wrap them in [`std::io::BufReader`] when reading from files.
+    /// Use [`DiffBuilder::source_reader`] and [`DiffBuilder::target_reader`] to reject duplicate keys.
+    ///
+    /// ```rust,no_run
+    /// use std::fs::File;
+    /// use std::io::BufReader;
+    ///
+    /// let source = BufReader::new(File::open("source.json").unwrap());
+    /// let target = BufReader::new(File::open("target.json").unwrap());
+    /// let diff = sjdiff::Diff::from_readers(source, target)
+    ///     .unwrap()
+    ///     .ignore_path("updated_at")
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn from_readers(source: impl Read, target: impl Read) -> Result<DiffBuilder, DiffBuilderError> {
+        let mut builder = DiffBuilder::default();
+        builder.source(parse("source", source, false)?).target(parse("target", target, false)?);
+        Ok(builder)
+    }
+}
+
+impl DiffBuilder {
+    /// If true [`DiffBuilder::source_reader`] and [`DiffBuilder::target_reader`] report duplicate keys
+    /// of objects as [`DiffBuilderError::Parse`], instead of keeping the last value of the key
+    /// like `serde_json` does, so data-quality issues a plain diff would hide aren't missed.
+    /// It has to be set before the readers, the whole input is read into memory to be checked then.
+    pub fn reject_duplicate_keys(&mut self, value: bool) -> &mut Self {
+        self.reject_duplicate_keys = value;
+        self
+    }
+
+    /// Sets `source` to the JSON parsed from `reader`, see [`Diff::from_readers`].
+    /// If parsing fails, [`DiffBuilder::build`] returns [`DiffBuilderError::Parse`].
+    pub fn source_reader(&mut self, reader: impl Read) -> &mut Self {
+        match parse("source", reader, self.reject_duplicate_keys) {
+            Ok(value) => self.source = Some(value),
+            Err(err) => self.errors.push(err),
+        }
+        self
+    }
+
+    /// Sets `target` to the JSON parsed from `reader`, see [`DiffBuilder::source_reader`].
+    pub fn target_reader(&mut self, reader: impl Read) -> &mut Self {
+        match parse("target", reader, self.reject_duplicate_keys) {
+            Ok(value) => self.target = Some(value),
+            Err(err) => self.errors.push(err),
+        }
+        self
+    }
+}
+
+fn parse(field: &'static str, mut reader: impl Read, reject_duplicate_keys: bool) -> Result<serde_json::Value, DiffBuilderError> {
+    let error = |error: String| DiffBuilderError::Parse { field, error };
+    if !reject_duplicate_keys {
+        return serde_json::from_reader(reader).map_err(|err| error(err.to_string()));
+    }
+
+    // The input is walked twice, as values can't be built by a custom visitor without losing
+    // the numbers of `serde_json/arbitrary_precision`.
+    let mut input = vec![];
+    reader.read_to_end(&mut input).map_err(|err| error(err.to_string()))?;
+    let mut deserializer = serde_json::Deserializer::from_slice(&input);
+    UniqueKeys { path: &mut Path::default() }
+        .deserialize(&mut deserializer)
+        .and_then(|_| deserializer.end())
+        .map_err(|err| error(err.to_string()))?;
+
+    serde_json::from_slice(&input).map_err(|err| error(err.to_string()))
+}
+
+/// Walks a JSON value and fails at the first object that has a duplicate key.
+struct UniqueKeys<'a> {
+    path: &'a mut Path,
+}
+
+impl<'de> DeserializeSeed<'de> for UniqueKeys<'_> {
+    type Value = ();
+
+    fn deserialize<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for UniqueKeys<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a JSON value")
+    }
+
+    fn visit_bool<E>(self, _: bool) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_i64<E>(self, _: i64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_u64<E>(self, _: u64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_f64<E>(self, _: f64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_str<E>(self, _: &str) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_unit<E>(self) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        let mut idx = 0;
+        loop {
+            self.path.push(PathElement::ArrayIndex(ArrayIndex::Index(idx)));
+            let element = seq.next_element_seed(UniqueKeys { path: self.path })?;
+            self.path.pop();
+            if element.is_none() {
+                return Ok(());
+            }
+            idx += 1;
+        }
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        let mut keys = HashSet::new();
+        while let Some(key) = map.next_key::<String>()? {
+            self.path.push(PathElement::Key(key.clone()));
+            if !keys.insert(key) {
+                return Err(de::Error::custom(format!("duplicate key at '{}'", json_pointer(self.path))));
+            }
+            map.next_value_seed(UniqueKeys { path: self.path })?;
+            self.path.pop();
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use crate::{Diff, DiffBuilder, DiffBuilderError};
+
+    #[test]
+    fn from_readers() {
+        let diff = Diff::from_readers(r#"{"a": 1, "b": [1]}"#.as_bytes(), r#"{"a": 2, "b": [1]}"#.as_bytes())
+            .unwrap()
+            .build()
+            .unwrap()
+            .compare()
+            .unwrap();
+        assert_eq!(diff.to_text(), "~ a: 1 => 2");
+
+        let err = Diff::from_readers("{}".as_bytes(), "{".as_bytes()).err();
+        assert!(matches!(err, Some(DiffBuilderError::Parse { field: "target", .. })));
+    }
+
+    #[test]
+    fn reject_duplicate_keys() {
+        let source = r#"{"users": [{"id": 1}, {"id": 2, "id": 3}], "n": 1.5}"#;
+        let diff = DiffBuilder::default()
+            .source_reader(source.as_bytes())
+            .target(json!({"users": [{"id": 1}, {"id": 3}], "n": 1.5}))
+            .build()
+            .unwrap();
+        assert!(diff.compare().is_none());
+
+        let err = DiffBuilder::default()
+            .reject_duplicate_keys(true)
+            .source_reader(source.as_bytes())
+            .target_reader(r#"{"users": [{"id": 1}], "n": 1.5}"#.as_bytes())
+            .build()
+            .unwrap_err();
+        let DiffBuilderError::Parse { field: "source", error } = err else { panic!("{:?}", err) };
+        assert!(error.starts_with("duplicate key at '/users/1/id'"), "{}", error);
+    }
+}
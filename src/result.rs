@@ -0,0 +1,160 @@
+use std::time::{Duration, Instant};
+use crate::{CompareError, Diff, DiffStats, Difference, IgnorePath, Path, Unchanged};
+
+/// The difference returned by [`Diff::compare_full`] along with the metadata of the comparison,
+/// e.g. for reporting pipelines.
+#[derive(Debug, Clone)]
+pub struct DiffResult {
+    /// The difference between `source` and `target` or `None` if they are equal
+    pub difference: Option<Difference>,
+    /// Counts of the differences, all of them are zero if the values are equal
+    pub stats: DiffStats,
+    /// The time it took to compare the values
+    pub duration: Duration,
+    /// Ignore paths that didn't match any of the compared values, see [`Diff::unused_ignore_paths`]
+    pub unused_ignore_paths: Vec<IgnorePath>,
+    /// Sorted paths of the scalar values that were compared and are equal, empty unless
+    /// [`DiffBuilder::include_unchanged`](crate::DiffBuilder::include_unchanged) is set.
+    /// Array elements have their index in `source`.
+    pub unchanged_paths: Vec<Path>,
+    /// The amount of the scalar values that were compared and are equal, they are counted
+    /// even if [`DiffBuilder::include_unchanged`](crate::DiffBuilder::include_unchanged) isn't set.
+    pub unchanged_leaves: usize,
+}
+
+impl DiffResult {
+    /// Returns the fraction of the compared leaves that differ, from `0.0` if the values are equal
+    /// to `1.0` if every leaf differs, e.g. for data-quality gates over documents of varying size.
+    /// Compared leaves are the leaf differences counted by [`DiffStats::total`] and [`DiffResult::unchanged_leaves`],
+    /// so an entry that only one of the values has counts as a single leaf, however big it is.
+    /// Ignored values aren't counted, `0.0` is returned if nothing was compared.
+    pub fn change_ratio(&self) -> f64 {
+        let changed = self.stats.total();
+        match changed + self.unchanged_leaves {
+            0 => 0.0,
+            total => changed as f64 / total as f64,
+        }
+    }
+}
+
+impl Diff {
+    /// Does the same as [`Diff::compare`], but also returns the [`DiffStats`] of the difference,
+    /// the time the comparison took, the unused ignore paths and the amount of equal values in a [`DiffResult`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the comparison fails, use [`Diff::try_compare_full`] for untrusted input.
+    pub fn compare_full(self) -> DiffResult {
+        match self.try_compare_full() {
+            Ok(result) => result,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    /// Does the same as [`Diff::compare_full`], but returns a [`CompareError`] instead of panicking
+    /// when the values can't be compared.
+    pub fn try_compare_full(mut self) -> Result<DiffResult, CompareError> {
+        // Unchanged values are always counted for `DiffResult::change_ratio`.
+        self.record_unchanged = true;
+        let start = Instant::now();
+        let difference = self.try_compare_ref()?;
+        let duration = start.elapsed();
+
+        let Unchanged { leaves: unchanged_leaves, paths: mut unchanged_paths } =
+            std::mem::take(&mut *self.unchanged_values.0.lock().unwrap());
+        unchanged_paths.sort_by(|a, b| a.0.cmp(&b.0));
+
+        Ok(DiffResult {
+            stats: difference.as_ref().map(Difference::stats).unwrap_or_default(),
+            difference,
+            duration,
+            unused_ignore_paths: self.unused_ignore_paths().into_iter().cloned().collect(),
+            unchanged_paths,
+            unchanged_leaves,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use crate::{ArrayAlignment, DiffBuilder};
+
+    #[test]
+    fn compare_full() {
+        let result = DiffBuilder::default()
+            .ignore_path("updated_at")
+            .ignore_path("created_at")
+            .source(json!({"name": "Joe", "age": 31, "updated_at": 1}))
+            .target(json!({"name": "Jim", "age": "31", "updated_at": 2, "email": "x@y.com"}))
+            .build()
+            .unwrap()
+            .compare_full();
+
+        assert_eq!(result.difference.unwrap().to_text(), ["~ age: 31 => \"31\"", "~ name: \"Joe\" => \"Jim\"", "+ email: \"x@y.com\""].join("\n"));
+        assert_eq!((result.stats.scalar_changes, result.stats.type_changes, result.stats.missing_entries), (1, 1, 1));
+        let unused: Vec<_> = result.unused_ignore_paths.iter().map(|p| p.path.to_string()).collect();
+        assert_eq!(unused, vec!["created_at"]);
+
+        let result = DiffBuilder::default().source(json!(1)).target(json!(1)).build().unwrap().compare_full();
+        assert!(result.difference.is_none());
+        assert_eq!(result.stats.total(), 0);
+    }
+
+    #[test]
+    fn include_unchanged() {
+        let result = DiffBuilder::default()
+            .include_unchanged(true)
+            .ignore_path("updated_at")
+            .array_alignment(ArrayAlignment::Lcs)
+            .source(json!({"name": "Joe", "age": 31, "updated_at": 1, "tags": ["a", "b"], "address": {"city": "X", "zip": null}}))
+            .target(json!({"name": "Jim", "age": 31, "updated_at": 2, "tags": ["c", "a", "b"], "address": {"city": "X", "zip": null}}))
+            .build()
+            .unwrap()
+            .compare_full();
+
+        let unchanged: Vec<_> = result.unchanged_paths.iter().map(ToString::to_string).collect();
+        assert_eq!(unchanged, ["address.city", "address.zip", "age", "tags.[0]", "tags.[1]"]);
+
+        let result = DiffBuilder::default().source(json!({"a": 1})).target(json!({"a": 1})).build().unwrap().compare_full();
+        assert!(result.unchanged_paths.is_empty());
+        assert_eq!(result.unchanged_leaves, 1);
+
+        // Only `compare_full` returns them, other comparisons don't record them.
+        let diff = DiffBuilder::default().include_unchanged(true).source(json!({"a": 1})).target(json!({"a": 1})).build().unwrap();
+        assert!(diff.compare_ref().is_none());
+        assert_eq!(diff.unchanged_values.0.lock().unwrap().leaves, 0);
+    }
+
+    #[test]
+    fn change_ratio() {
+        let result = DiffBuilder::default()
+            .ignore_path("updated_at")
+            .array_alignment(ArrayAlignment::Lcs)
+            .source(json!({"name": "Joe", "age": 31, "updated_at": 1, "tags": ["a", "b"], "address": {"city": "X", "zip": 1}}))
+            .target(json!({"name": "Jim", "age": 31, "updated_at": 2, "tags": ["c", "a", "b"], "address": {"city": "Y", "zip": 1}}))
+            .build()
+            .unwrap()
+            .compare_full();
+
+        // `name`, `address.city` and the inserted tag differ, `age`, `address.zip` and two tags are equal.
+        assert_eq!((result.stats.total(), result.unchanged_leaves), (3, 4));
+        assert!((result.change_ratio() - 3.0 / 7.0).abs() < f64::EPSILON);
+
+        // Pairs of elements that are only tried out while matching aren't counted.
+        let result = DiffBuilder::default()
+            .unordered_array("tags")
+            .array_alignment(ArrayAlignment::Lcs)
+            .source(json!({"tags": ["a", "b"], "items": [{"x": 1, "y": 2}, {"x": 1, "y": 2}]}))
+            .target(json!({"tags": ["b", "c", "a"], "items": [{"x": 1, "y": 3}, {"x": 1, "y": 2}]}))
+            .build()
+            .unwrap()
+            .compare_full();
+        assert_eq!((result.stats.total(), result.unchanged_leaves), (2, 5));
+
+        let result = DiffBuilder::default().source(json!({})).target(json!({})).build().unwrap().compare_full();
+        assert_eq!(result.change_ratio(), 0.0);
+        let result = DiffBuilder::default().source(json!({"a": 1})).target(json!({"b": 1})).build().unwrap().compare_full();
+        assert_eq!(result.change_ratio(), 1.0);
+    }
+}
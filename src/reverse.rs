@@ -0,0 +1,215 @@
+use crate::apply::{aligned_insertions, aligned_removals};
+use crate::string_edits::string_edits;
+use crate::{ArrayDifference, ArrayOp, Difference, EntryDifference, KeyOrder, KeyedPair, Map, MovedElement, ScalarDifference};
+
+impl Difference {
+    /// Returns the difference of `target` to `source`, i.e. the one [`Diff::compare`](crate::Diff::compare)
+    /// returns when the values are swapped, without comparing them again.
+    ///
+    /// Missing and extra entries are swapped, [`ArrayDifference::Shorter`] becomes
+    /// [`ArrayDifference::Longer`] and vice versa. The order of the entries may differ
+    /// from the order of a recomputed difference.
+    pub fn reverse(&self) -> Difference {
+        match self {
+            Difference::Scalar(scalar) => Difference::Scalar(match scalar {
+                ScalarDifference::Bool { source, target } => ScalarDifference::Bool {
+                    source: *target,
+                    target: *source,
+                },
+                ScalarDifference::String { source, target, edits } => ScalarDifference::String {
+                    source: target.clone(),
+                    target: source.clone(),
+                    edits: edits.as_ref().map(|_| string_edits(target, source)),
+                },
+                ScalarDifference::Number { source, target, number_kind } => ScalarDifference::Number {
+                    source: target.clone(),
+                    target: source.clone(),
+                    number_kind: number_kind.reverse(),
+                },
+            }),
+            Difference::Type { source_type, source_value, target_type, target_value } => Difference::Type {
+                source_type: *target_type,
+                source_value: target_value.clone(),
+                target_type: *source_type,
+                target_value: source_value.clone(),
+            },
+            Difference::Array(array) => Difference::Array(reverse_array(array)),
+            Difference::Object { different_entries, truncated_count, key_order } => Difference::Object {
+                different_entries: Map(different_entries.0
+                    .iter()
+                    .map(|(key, entry)| {
+                        let entry = match entry {
+                            EntryDifference::Missing { value } => EntryDifference::Extra { value: value.clone() },
+                            EntryDifference::Extra { value } => EntryDifference::Missing { value: value.clone() },
+                            EntryDifference::Value { value_diff } => EntryDifference::Value { value_diff: value_diff.reverse() },
+                        };
+                        (key.clone(), entry)
+                    })
+                    .collect()),
+                truncated_count: *truncated_count,
+                key_order: key_order.as_ref().map(|order| KeyOrder { source: order.target.clone(), target: order.source.clone() }),
+            },
+        }
+    }
+}
+
+fn reverse_array(array: &ArrayDifference) -> ArrayDifference {
+    match array {
+        ArrayDifference::PairsOnly { different_pairs } => ArrayDifference::PairsOnly {
+            different_pairs: reverse_pairs(different_pairs),
+        },
+        ArrayDifference::Shorter { different_pairs, source_length, missing_elements, truncated_count } => {
+            ArrayDifference::Longer {
+                different_pairs: different_pairs.as_ref().map(reverse_pairs),
+                target_length: *source_length,
+                extra_length: missing_elements.len() + truncated_count,
+                extra_elements: missing_elements.clone(),
+                truncated_count: *truncated_count,
+            }
+        }
+        ArrayDifference::Longer { different_pairs, target_length, extra_elements, truncated_count, .. } => {
+            ArrayDifference::Shorter {
+                different_pairs: different_pairs.as_ref().map(reverse_pairs),
+                source_length: *target_length,
+                missing_elements: extra_elements.clone(),
+                truncated_count: *truncated_count,
+            }
+        }
+        ArrayDifference::Keyed { different_pairs, missing_elements, extra_elements } => ArrayDifference::Keyed {
+            different_pairs: different_pairs
+                .iter()
+                .map(|pair| KeyedPair {
+                    key: pair.key.clone(),
+                    source_index: pair.target_index,
+                    target_index: pair.source_index,
+                    difference: pair.difference.reverse(),
+                })
+                .collect(),
+            missing_elements: clone_elements(extra_elements),
+            extra_elements: clone_elements(missing_elements),
+        },
+        ArrayDifference::Aligned { different_pairs, removed_elements, inserted_elements, moved_elements } => {
+            // Elements that are neither removed nor inserted are aligned in order,
+            // so the n-th of them in `source` is paired with the n-th of them in `target`.
+            let removals = aligned_removals(removed_elements, moved_elements);
+            let insertions: Vec<_> = aligned_insertions(inserted_elements, moved_elements)
+                .into_iter()
+                .map(|(idx, _)| idx)
+                .collect();
+            let different_pairs = different_pairs.0
+                .iter()
+                .map(|(source_index, difference)| {
+                    let aligned = source_index - removals.iter().filter(|idx| *idx < source_index).count();
+                    (aligned_index(aligned, &insertions), difference.reverse())
+                })
+                .collect();
+
+            ArrayDifference::Aligned {
+                different_pairs: Map(different_pairs),
+                removed_elements: clone_elements(inserted_elements),
+                inserted_elements: clone_elements(removed_elements),
+                moved_elements: moved_elements
+                    .iter()
+                    .map(|moved| MovedElement {
+                        source_index: moved.target_index,
+                        target_index: moved.source_index,
+                        value: moved.value.clone(),
+                    })
+                    .collect(),
+            }
+        }
+        ArrayDifference::Ops { ops } => {
+            // Indices of inserted elements are in `target`, which becomes the `source` of the reversed ops.
+            let mut target_index = 0;
+            let ops = ops
+                .iter()
+                .map(|op| match op {
+                    ArrayOp::Keep { count } => {
+                        target_index += count;
+                        ArrayOp::Keep { count: *count }
+                    }
+                    ArrayOp::Replace { difference, .. } => {
+                        target_index += 1;
+                        ArrayOp::Replace { index: target_index - 1, difference: difference.reverse() }
+                    }
+                    ArrayOp::Insert { index, value } => {
+                        target_index += 1;
+                        ArrayOp::Delete { index: *index, value: value.clone() }
+                    }
+                    ArrayOp::Delete { index, value } => ArrayOp::Insert { index: *index, value: value.clone() },
+                })
+                .collect();
+
+            ArrayDifference::Ops { ops }
+        }
+    }
+}
+
+fn reverse_pairs(pairs: &Map<usize, Difference>) -> Map<usize, Difference> {
+    Map(pairs.0.iter().map(|(idx, difference)| (*idx, difference.reverse())).collect())
+}
+
+fn clone_elements(elements: &Map<usize, serde_json::Value>) -> Map<usize, serde_json::Value> {
+    Map(elements.0.clone())
+}
+
+/// Returns the index of the `aligned`-th element whose index is not in `skipped`.
+/// `skipped` must be sorted.
+fn aligned_index(aligned: usize, skipped: &[usize]) -> usize {
+    let mut idx = aligned;
+    for skipped_idx in skipped {
+        if *skipped_idx <= idx {
+            idx += 1;
+        } else {
+            break;
+        }
+    }
+
+    idx
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use crate::{ArrayAlignment, DiffBuilder, DiffStats};
+
+    #[test]
+    fn reverse() {
+        let cases = [
+            (json!({"name": "Joe", "age": 31, "ok": true}), json!({"age": 33, "email": "joe@example.com", "ok": false})),
+            (json!({"users": [{"age": 1}, {"age": 2}]}), json!({"users": [{"age": 1}, {"age": 3}, {"age": 4}]})),
+            (json!({"users": [{"age": 1}, {"age": 2}, {"age": 3}]}), json!({"users": [{"age": 0}]})),
+            (json!([1, 2, 3, 4, 5, 6]), json!([0, 1, 7, 3, 8, 9, 5])),
+            (json!(["a", "b", "c", "d", "e"]), json!(["d", "a", "b", "x", "c", "e"])),
+            (json!({"a": {"b": [true, "x"]}}), json!({"a": {"b": [false, 1]}})),
+            (json!({"items": [{"id": 1, "n": 1}, {"id": 2}]}), json!({"items": [{"id": 3}, {"id": 1, "n": 2}]})),
+            (json!(1), json!("1")),
+        ];
+
+        for (source, target) in cases {
+            for array_alignment in [ArrayAlignment::Index, ArrayAlignment::Lcs] {
+                let compare = |source: &serde_json::Value, target: &serde_json::Value| {
+                    DiffBuilder::default()
+                        .array_alignment(array_alignment)
+                        .detect_moved_elements(true)
+                        .array_key("items", "id")
+                        .source(source.clone())
+                        .target(target.clone())
+                        .build()
+                        .unwrap()
+                        .compare()
+                        .unwrap()
+                };
+                let reversed = compare(&source, &target).reverse();
+
+                let mut patched = target.clone();
+                reversed.apply(&mut patched).unwrap();
+                assert_eq!(patched, source);
+
+                let expected = compare(&target, &source).stats();
+                assert_eq!(reversed.stats(), expected);
+                assert_ne!(expected, DiffStats::default());
+            }
+        }
+    }
+}
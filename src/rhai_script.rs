@@ -1,6 +1,24 @@
+//! Helpers of [`crate::IgnorePathCondition::Rhai`] scripts.
+//!
+//! The scope of every script contains the following variables:
+//! - `source` and `target` – the whole compared values;
+//! - `curr_path` – the [`Path`] of the compared value with the real indices of array elements,
+//!   to be passed to `value_by_path`;
+//! - `current_path_string` – `curr_path` rendered as a string, e.g. `users.[1].age`,
+//!   so scripts can branch on exact locations;
+//! - `source_value` and `target_value` – the values at `curr_path`, `()` if the value is missing,
+//!   e.g. `source_value != target_value && target_value == "archived"`.
+
 use crate::{ArrayIndex, Path, PathElement};
 use crate::element_path_parser::parse_element_path;
 
+/// Converts a value to be pushed to the rhai scope, a missing value becomes `()`.
+pub(crate) fn to_dynamic(value: Option<&serde_json::Value>) -> rhai::Dynamic {
+    value
+        .and_then(|value| rhai::serde::to_dynamic(value).ok())
+        .unwrap_or(rhai::Dynamic::UNIT)
+}
+
 /// Should be used only in rhai scope.
 /// A method will be part of object map and receive two arguments:
 /// `path` – string typed path which should be ignored. It will be parsed
@@ -56,6 +74,7 @@ pub(crate) fn value_by_path(source_obj: rhai::Dynamic, path: &str, curr_path: Pa
                 }
                 _ => return rhai::Dynamic::from(()),
             },
+            PathElement::KeyPattern(_) | PathElement::AnyDepth => return rhai::Dynamic::from(()),
         }
     }
 
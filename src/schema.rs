@@ -0,0 +1,227 @@
+use crate::{ArrayIndex, DiffBuilder, IgnorePathBuilder, Path, PathElement};
+
+impl DiffBuilder {
+    /// Walks a JSON Schema and ignores every property that carries `annotation`,
+    /// e.g. `readOnly` or a custom `x-volatile`, the same way as [`DiffBuilder::ignore_path`].
+    /// An annotation set to `false` or `null` doesn't count.
+    ///
+    /// Nested `properties`, the `items` of arrays and the subschemas of `allOf`, `anyOf` and `oneOf`
+    /// are walked, references like `$ref` aren't resolved.
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    /// use sjdiff::DiffBuilder;
+    ///
+    /// let schema = json!({
+    ///     "type": "object",
+    ///     "properties": {
+    ///         "id": {"type": "string", "readOnly": true},
+    ///         "users": {"type": "array", "items": {"properties": {"updated_at": {"readOnly": true}}}}
+    ///     }
+    /// });
+    ///
+    /// let diff = DiffBuilder::default()
+    ///     .ignore_paths_from_schema(&schema, "readOnly")
+    ///     .source(json!({"id": "a", "users": [{"updated_at": 1}]}))
+    ///     .target(json!({"id": "b", "users": [{"updated_at": 2}]}))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// assert!(diff.compare().is_none());
+    /// ```
+    pub fn ignore_paths_from_schema(&mut self, schema: &serde_json::Value, annotation: &str) -> &mut Self {
+        let mut paths = vec![];
+        annotated_paths(schema, annotation, &mut Path::default(), &mut paths);
+
+        let ignore_paths = self.ignore_paths.get_or_insert_with(Vec::new);
+        for path in paths {
+            ignore_paths.push(IgnorePathBuilder::default().path(path).build().unwrap());
+        }
+        self
+    }
+
+    /// Walks a JSON Schema and matches the elements of every array annotated with `x-array-key`
+    /// by the key field it names, the same way as [`DiffBuilder::array_key`]. Annotations that aren't
+    /// strings are skipped. Schemas are walked like in [`DiffBuilder::ignore_paths_from_schema`],
+    /// the `items` of keyed arrays as well, so nested arrays can have their own keys.
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    /// use sjdiff::DiffBuilder;
+    ///
+    /// let schema = json!({
+    ///     "properties": {
+    ///         "users": {"type": "array", "x-array-key": "id", "items": {"type": "object"}}
+    ///     }
+    /// });
+    ///
+    /// let diff = DiffBuilder::default()
+    ///     .array_keys_from_schema(&schema)
+    ///     .source(json!({"users": [{"id": 1}, {"id": 2}]}))
+    ///     .target(json!({"users": [{"id": 2}, {"id": 1}]}))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// assert!(diff.compare().is_none());
+    /// ```
+    pub fn array_keys_from_schema(&mut self, schema: &serde_json::Value) -> &mut Self {
+        let mut keys = vec![];
+        array_key_paths(schema, &mut Path::default(), &mut keys);
+
+        self.array_keys.get_or_insert_with(Vec::new).extend(keys);
+        self
+    }
+}
+
+/// Pushes the paths of all the properties of `schema` at `curr_path` annotated with `annotation` to `paths`.
+fn annotated_paths(schema: &serde_json::Value, annotation: &str, curr_path: &mut Path, paths: &mut Vec<Path>) {
+    let Some(schema) = schema.as_object() else {
+        return;
+    };
+
+    if let Some(properties) = schema.get("properties").and_then(|properties| properties.as_object()) {
+        for (key, property) in properties {
+            curr_path.push(PathElement::Key(key.clone()));
+            if property.get(annotation).is_some_and(|value| !matches!(value, serde_json::Value::Bool(false) | serde_json::Value::Null)) {
+                paths.push(curr_path.clone());
+            } else {
+                annotated_paths(property, annotation, curr_path, paths);
+            }
+            curr_path.pop();
+        }
+    }
+
+    match schema.get("items") {
+        Some(serde_json::Value::Array(items)) => {
+            for (idx, item) in items.iter().enumerate() {
+                curr_path.push(PathElement::ArrayIndex(ArrayIndex::Index(idx)));
+                annotated_paths(item, annotation, curr_path, paths);
+                curr_path.pop();
+            }
+        }
+        Some(items) => {
+            curr_path.push(PathElement::ArrayIndex(ArrayIndex::All));
+            annotated_paths(items, annotation, curr_path, paths);
+            curr_path.pop();
+        }
+        None => {}
+    }
+
+    for keyword in ["allOf", "anyOf", "oneOf"] {
+        for subschema in schema.get(keyword).and_then(|subschemas| subschemas.as_array()).into_iter().flatten() {
+            annotated_paths(subschema, annotation, curr_path, paths);
+        }
+    }
+}
+
+/// Pushes the paths of all the arrays of `schema` at `curr_path` annotated with `x-array-key`
+/// along with their key fields to `keys`.
+fn array_key_paths(schema: &serde_json::Value, curr_path: &mut Path, keys: &mut Vec<(Path, String)>) {
+    let Some(schema) = schema.as_object() else {
+        return;
+    };
+
+    if let Some(key_field) = schema.get("x-array-key").and_then(|key_field| key_field.as_str()) {
+        keys.push((curr_path.clone(), key_field.to_string()));
+    }
+
+    if let Some(properties) = schema.get("properties").and_then(|properties| properties.as_object()) {
+        for (key, property) in properties {
+            curr_path.push(PathElement::Key(key.clone()));
+            array_key_paths(property, curr_path, keys);
+            curr_path.pop();
+        }
+    }
+
+    match schema.get("items") {
+        Some(serde_json::Value::Array(items)) => {
+            for (idx, item) in items.iter().enumerate() {
+                curr_path.push(PathElement::ArrayIndex(ArrayIndex::Index(idx)));
+                array_key_paths(item, curr_path, keys);
+                curr_path.pop();
+            }
+        }
+        Some(items) => {
+            curr_path.push(PathElement::ArrayIndex(ArrayIndex::All));
+            array_key_paths(items, curr_path, keys);
+            curr_path.pop();
+        }
+        None => {}
+    }
+
+    for keyword in ["allOf", "anyOf", "oneOf"] {
+        for subschema in schema.get(keyword).and_then(|subschemas| subschemas.as_array()).into_iter().flatten() {
+            array_key_paths(subschema, curr_path, keys);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use crate::DiffBuilder;
+
+    #[test]
+    fn ignore_paths_from_schema() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "id": {"type": "string", "x-volatile": true},
+                "name": {"type": "string", "x-volatile": false},
+                "address": {"allOf": [{"properties": {"zip": {"x-volatile": true}}}]},
+                "users": {"type": "array", "items": {"properties": {"seen": {"x-volatile": {"reason": "clock"}}}}},
+                "pair": {"type": "array", "items": [{"x-volatile": true}, {"properties": {"at": {"x-volatile": true}}}]}
+            }
+        });
+
+        let diff = DiffBuilder::default()
+            .ignore_paths_from_schema(&schema, "x-volatile")
+            .source(json!({"id": 1, "name": "Joe", "address": {"zip": 1}, "users": [{"seen": 1}], "pair": [1, {"at": 1}]}))
+            .target(json!({"id": 2, "name": "Jim", "address": {"zip": 2}, "users": [{"seen": 2}], "pair": [1, {"at": 2}]}))
+            .build()
+            .unwrap();
+
+        let paths: Vec<_> = diff.ignore_paths.iter().map(|p| p.path.to_string()).collect();
+        assert_eq!(paths, vec!["address.zip", "id", "pair.[1].at", "users.[_].seen"]);
+        assert_eq!(diff.compare().unwrap().to_text(), "~ name: \"Joe\" => \"Jim\"");
+    }
+
+    #[test]
+    fn array_keys_from_schema() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "users": {
+                    "type": "array",
+                    "x-array-key": "id",
+                    "items": {"properties": {"pets": {"type": "array", "x-array-key": "name"}}}
+                },
+                "teams": {"allOf": [{"type": "array", "x-array-key": "code"}]},
+                "tags": {"type": "array", "x-array-key": 1}
+            }
+        });
+
+        let diff = DiffBuilder::default()
+            .array_keys_from_schema(&schema)
+            .source(json!({
+                "users": [{"id": 1, "pets": [{"name": "a", "age": 1}, {"name": "b"}]}, {"id": 2}],
+                "teams": [{"code": "x"}, {"code": "y"}],
+                "tags": ["a", "b"]
+            }))
+            .target(json!({
+                "users": [{"id": 2}, {"id": 1, "pets": [{"name": "b"}, {"name": "a", "age": 2}]}],
+                "teams": [{"code": "y"}, {"code": "x"}],
+                "tags": ["b", "a"]
+            }))
+            .build()
+            .unwrap();
+
+        let keys: Vec<_> = diff.array_keys.iter().map(|(path, key)| format!("{}: {}", path, key)).collect();
+        assert_eq!(keys, vec!["teams: code", "users: id", "users.[_].pets: name"]);
+        assert_eq!(diff.compare().unwrap().to_text(), [
+            "~ tags.[0]: \"a\" => \"b\"",
+            "~ tags.[1]: \"b\" => \"a\"",
+            "~ users.[0].pets.[0].age: 1 => 2",
+        ].join("\n"));
+    }
+}
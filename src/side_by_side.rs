@@ -0,0 +1,100 @@
+use serde_json::json;
+use crate::text::display_path;
+use crate::{ArrayIndex, Difference, LeafDifference, PathElement};
+
+impl Difference {
+    /// Renders the difference as two columns, values of `source` on the left and values
+    /// of `target` on the right, with one row per leaf difference, e.g.:
+    ///
+    /// ```text
+    /// source                          | target
+    /// - users.[1].animals.type: "dog" | + users.[1].animals.type: "cat"
+    /// - age: 31                       |
+    ///                                 | + email: "x@y.com"
+    /// - tags.[3]: "a"                 | + tags.[0]: "a"
+    /// ```
+    ///
+    /// `-` marks the values of `source` that differ, `+` the values of `target`.
+    /// Entries that only one of them has leave the other column empty, array elements
+    /// moved to another index are shown at their index in each of them, and objects
+    /// with reordered keys show the order of the keys in each of them.
+    pub fn to_side_by_side(&self) -> String {
+        let rows: Vec<_> = self.flatten()
+            .iter()
+            .map(|(path, leaf)| {
+                let side = |marker, path, value: &dyn std::fmt::Display| format!("{} {}: {}", marker, display_path(path), value);
+                match leaf {
+                    LeafDifference::Scalar(scalar) => (
+                        side('-', path, &scalar.source_value()),
+                        side('+', path, &scalar.target_value()),
+                    ),
+                    LeafDifference::Type { source_value, target_value, .. } => {
+                        (side('-', path, source_value), side('+', path, target_value))
+                    }
+                    LeafDifference::Missing(value) => (String::new(), side('+', path, value)),
+                    LeafDifference::Extra(value) => (side('-', path, value), String::new()),
+                    LeafDifference::Moved { target_index, value } => {
+                        let mut target_path = path.clone();
+                        target_path.pop();
+                        target_path.push(PathElement::ArrayIndex(ArrayIndex::Index(*target_index)));
+                        (side('-', path, value), side('+', &target_path, value))
+                    }
+                    LeafDifference::Reordered(key_order) => {
+                        (side('-', path, &json!(key_order.source)), side('+', path, &json!(key_order.target)))
+                    }
+                }
+            })
+            .collect();
+
+        let width = rows.iter()
+            .map(|(source, _)| source.chars().count())
+            .max()
+            .unwrap_or(0)
+            .max("source".len());
+
+        std::iter::once(("source".to_string(), "target".to_string()))
+            .chain(rows)
+            .map(|(source, target)| format!("{:<width$} | {}", source, target).trim_end().to_string())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use crate::{ArrayAlignment, DiffBuilder};
+
+    #[test]
+    fn to_side_by_side() {
+        let diff = DiffBuilder::default()
+            .source(json!({"age": 31, "users": [{"animals": {"type": "dog"}}], "id": 1}))
+            .target(json!({"email": "x@y.com", "users": [{"animals": {"type": "cat"}}], "id": "1"}))
+            .build()
+            .unwrap()
+            .compare()
+            .unwrap();
+
+        assert_eq!(
+            diff.to_side_by_side(),
+            [
+                "source                          | target",
+                "- age: 31                       |",
+                "- id: 1                         | + id: \"1\"",
+                "- users.[0].animals.type: \"dog\" | + users.[0].animals.type: \"cat\"",
+                "                                | + email: \"x@y.com\"",
+            ].join("\n")
+        );
+
+        let diff = DiffBuilder::default()
+            .array_alignment(ArrayAlignment::Lcs)
+            .detect_moved_elements(true)
+            .source(json!(["a", "b", "c"]))
+            .target(json!(["c", "a", "b"]))
+            .build()
+            .unwrap()
+            .compare()
+            .unwrap();
+        assert_eq!(diff.to_side_by_side(), "source     | target\n- [2]: \"c\" | + [0]: \"c\"");
+    }
+}
@@ -0,0 +1,205 @@
+use serde::Serialize;
+use crate::{ArrayDifference, ArrayOp, Difference, EntryDifference, LeafDifference, Path};
+
+/// Counts of the differences aggregated across the whole [`Difference`] tree,
+/// see [`Difference::stats`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct DiffStats {
+    /// Scalar values of the same type that are different
+    pub scalar_changes: usize,
+    /// Values that have different types
+    pub type_changes: usize,
+    /// Entries and array elements from `target` that `source` is missing, including the elements
+    /// omitted by [`DiffBuilder::max_reported_array_elements`](crate::DiffBuilder::max_reported_array_elements)
+    pub missing_entries: usize,
+    /// Entries and array elements that `source` has, and `target` doesn't, including the omitted elements
+    pub extra_entries: usize,
+    /// Arrays that have different lengths
+    pub array_length_mismatches: usize,
+    /// Array elements that were moved to another index
+    pub moved_elements: usize,
+    /// Objects whose keys are in another order, see [`DiffBuilder::ordered_object_keys`](crate::DiffBuilder::ordered_object_keys)
+    pub reordered_objects: usize,
+    /// Entry differences of objects omitted by [`DiffBuilder::max_entry_differences`](crate::DiffBuilder::max_entry_differences),
+    /// each of them is counted once as its kind isn't known
+    pub truncated_entries: usize,
+}
+
+impl DiffStats {
+    /// Returns the total amount of leaf differences. Array length mismatches are not included,
+    /// as they are already counted as missing or extra elements.
+    pub fn total(&self) -> usize {
+        self.scalar_changes + self.type_changes + self.missing_entries + self.extra_entries + self.moved_elements
+            + self.reordered_objects + self.truncated_entries
+    }
+}
+
+impl Difference {
+    /// Returns the counts of the differences aggregated across the whole tree.
+    pub fn stats(&self) -> DiffStats {
+        let mut stats = DiffStats::default();
+        for (_, leaf) in self.flatten() {
+            match leaf {
+                LeafDifference::Scalar(_) => stats.scalar_changes += 1,
+                LeafDifference::Type { .. } => stats.type_changes += 1,
+                LeafDifference::Missing(_) => stats.missing_entries += 1,
+                LeafDifference::Extra(_) => stats.extra_entries += 1,
+                LeafDifference::Moved { .. } => stats.moved_elements += 1,
+                LeafDifference::Reordered(_) => stats.reordered_objects += 1,
+            }
+        }
+        stats.array_length_mismatches = array_length_mismatches(self);
+        count_truncated(self, &mut stats);
+
+        stats
+    }
+
+    /// Returns the amount of leaf differences at `prefix` or inside it, e.g. `users` counts
+    /// the differences of all the users. Paths follow the rules of [`Difference::flatten`],
+    /// `prefix` may have wildcards like `[_]`, see [`Path::matches`]. The empty path counts all of them.
+    pub fn count_under(&self, prefix: &Path) -> usize {
+        self.flatten().iter().filter(|(path, _)| prefix.matches_or_contains(path)).count()
+    }
+}
+
+/// Adds the differences that are only counted in the `truncated_count` of the arrays and objects to `stats`.
+fn count_truncated(difference: &Difference, stats: &mut DiffStats) {
+    match difference {
+        Difference::Scalar(_) | Difference::Type { .. } => {}
+        Difference::Array(array) => match array {
+            ArrayDifference::PairsOnly { different_pairs } => {
+                different_pairs.0.iter().for_each(|(_, diff)| count_truncated(diff, stats));
+            }
+            ArrayDifference::Shorter { different_pairs, truncated_count, .. } => {
+                different_pairs.iter().flat_map(|pairs| pairs.0.iter()).for_each(|(_, diff)| count_truncated(diff, stats));
+                stats.missing_entries += truncated_count;
+            }
+            ArrayDifference::Longer { different_pairs, truncated_count, .. } => {
+                different_pairs.iter().flat_map(|pairs| pairs.0.iter()).for_each(|(_, diff)| count_truncated(diff, stats));
+                stats.extra_entries += truncated_count;
+            }
+            ArrayDifference::Keyed { different_pairs, .. } => {
+                different_pairs.iter().for_each(|pair| count_truncated(&pair.difference, stats));
+            }
+            ArrayDifference::Aligned { different_pairs, .. } => {
+                different_pairs.0.iter().for_each(|(_, diff)| count_truncated(diff, stats));
+            }
+            ArrayDifference::Ops { ops } => {
+                for op in ops {
+                    if let ArrayOp::Replace { difference, .. } = op {
+                        count_truncated(difference, stats);
+                    }
+                }
+            }
+        },
+        Difference::Object { different_entries, truncated_count, .. } => {
+            stats.truncated_entries += truncated_count;
+            for (_, entry) in &different_entries.0 {
+                if let EntryDifference::Value { value_diff } = entry {
+                    count_truncated(value_diff, stats);
+                }
+            }
+        }
+    }
+}
+
+fn array_length_mismatches(difference: &Difference) -> usize {
+    match difference {
+        Difference::Scalar(_) | Difference::Type { .. } => 0,
+        Difference::Array(array) => match array {
+            ArrayDifference::PairsOnly { different_pairs } => {
+                different_pairs.0.iter().map(|(_, diff)| array_length_mismatches(diff)).sum()
+            }
+            ArrayDifference::Shorter { different_pairs, .. } | ArrayDifference::Longer { different_pairs, .. } => {
+                let nested: usize = different_pairs.iter()
+                    .flat_map(|pairs| pairs.0.iter())
+                    .map(|(_, diff)| array_length_mismatches(diff))
+                    .sum();
+                nested + 1
+            }
+            ArrayDifference::Keyed { different_pairs, missing_elements, extra_elements } => {
+                let nested: usize = different_pairs.iter().map(|pair| array_length_mismatches(&pair.difference)).sum();
+                nested + usize::from(missing_elements.0.len() != extra_elements.0.len())
+            }
+            ArrayDifference::Aligned { different_pairs, removed_elements, inserted_elements, .. } => {
+                let nested: usize = different_pairs.0.iter().map(|(_, diff)| array_length_mismatches(diff)).sum();
+                nested + usize::from(removed_elements.0.len() != inserted_elements.0.len())
+            }
+            ArrayDifference::Ops { ops } => {
+                let (mut nested, mut inserted, mut deleted) = (0, 0, 0);
+                for op in ops {
+                    match op {
+                        ArrayOp::Keep { .. } => {}
+                        ArrayOp::Replace { difference, .. } => nested += array_length_mismatches(difference),
+                        ArrayOp::Insert { .. } => inserted += 1,
+                        ArrayOp::Delete { .. } => deleted += 1,
+                    }
+                }
+                nested + usize::from(inserted != deleted)
+            }
+        },
+        Difference::Object { different_entries, .. } => different_entries.0
+            .iter()
+            .map(|(_, entry)| match entry {
+                EntryDifference::Value { value_diff } => array_length_mismatches(value_diff),
+                EntryDifference::Missing { .. } | EntryDifference::Extra { .. } => 0,
+            })
+            .sum(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use crate::{DiffBuilder, DiffStats, Path};
+
+    #[test]
+    fn stats() {
+        let diff = DiffBuilder::default()
+            .source(json!({"age": 31, "id": 1, "name": "Joe", "tags": ["a", "b", "c"], "users": [{"pets": []}]}))
+            .target(json!({"age": 33, "id": "1", "email": "x@y.com", "tags": ["a"], "users": [{"pets": ["cat"]}]}))
+            .build()
+            .unwrap()
+            .compare()
+            .unwrap();
+
+        let stats = diff.stats();
+        assert_eq!(stats, DiffStats {
+            scalar_changes: 1,
+            type_changes: 1,
+            missing_entries: 2,
+            extra_entries: 3,
+            array_length_mismatches: 2,
+            moved_elements: 0,
+            reordered_objects: 0,
+            truncated_entries: 0,
+        });
+        assert_eq!(stats.total(), 7);
+
+        let count = |prefix: &str| diff.count_under(&prefix.parse().unwrap());
+        assert_eq!((count("tags"), count("users.[_].pets"), count("users.[1]"), count("age")), (2, 1, 0, 1));
+        assert_eq!(diff.count_under(&Path::default()), stats.total());
+
+        let diff = DiffBuilder::default()
+            .max_reported_array_elements(1)
+            .source(json!({"a": [1], "b": {"c": [1, 2, 3, 4]}}))
+            .target(json!({"a": [1, 2, 3], "b": {"c": [1]}}))
+            .build()
+            .unwrap()
+            .compare()
+            .unwrap();
+        let stats = diff.stats();
+        assert_eq!((stats.missing_entries, stats.extra_entries, stats.total()), (2, 3, 5));
+
+        let diff = DiffBuilder::default()
+            .max_entry_differences(1)
+            .source(json!({"a": 1, "b": 1, "c": {"x": 1, "y": 1}}))
+            .target(json!({"a": 2, "b": 2, "c": {"x": 2, "y": 2}}))
+            .build()
+            .unwrap()
+            .compare()
+            .unwrap();
+        let stats = diff.stats();
+        assert_eq!((stats.scalar_changes, stats.truncated_entries, stats.total()), (1, 2, 3));
+    }
+}
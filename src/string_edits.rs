@@ -0,0 +1,132 @@
+use std::ops::Range;
+use serde::Serialize;
+
+/// A replacement of a part of the `source` string that makes it closer to `target`,
+/// see [`DiffBuilder::string_subdiff`](crate::DiffBuilder::string_subdiff).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StringEdit {
+    /// Byte range of the replaced part of `source`, it's empty if the text is inserted
+    pub range: Range<usize>,
+    /// The text of `target` that replaces the range, it's empty if the text is deleted
+    pub replacement: String,
+}
+
+/// The max amount of cells of the LCS table, 16 MB, that [`string_edits`] fills.
+const MAX_LCS_CELLS: usize = 4 * 1024 * 1024;
+
+/// Returns the edits that turn `source` into `target`, ordered by their range.
+/// Strings are split into words and single non-alphanumeric characters, which are aligned
+/// by their longest common subsequence after the common prefix and suffix are skipped.
+/// If the rest is too long to be aligned within [`MAX_LCS_CELLS`], it's replaced by a single edit.
+pub(crate) fn string_edits(source: &str, target: &str) -> Vec<StringEdit> {
+    let (source_tokens, target_tokens) = (tokens(source), tokens(target));
+    let prefix = source_tokens.iter().zip(&target_tokens).take_while(|(a, b)| a == b).count();
+    let suffix = source_tokens[prefix..].iter()
+        .rev()
+        .zip(target_tokens[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let a = &source_tokens[prefix..source_tokens.len() - suffix];
+    let b = &target_tokens[prefix..target_tokens.len() - suffix];
+    let (n, m) = (a.len(), b.len());
+    let mut pos: usize = source_tokens[..prefix].iter().map(|token| token.len()).sum();
+    if (n + 1).saturating_mul(m + 1) > MAX_LCS_CELLS {
+        let end = pos + a.iter().map(|token| token.len()).sum::<usize>();
+        return vec![StringEdit { range: pos..end, replacement: b.concat() }];
+    }
+
+    // lcs[i * (m + 1) + j] is the length of the LCS of a[i..] and b[j..]
+    let mut lcs = vec![0u32; (n + 1) * (m + 1)];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i * (m + 1) + j] = if a[i] == b[j] {
+                lcs[(i + 1) * (m + 1) + j + 1] + 1
+            } else {
+                lcs[(i + 1) * (m + 1) + j].max(lcs[i * (m + 1) + j + 1])
+            };
+        }
+    }
+
+    let mut edits = vec![];
+    let mut pending: Option<StringEdit> = None;
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && a[i] == b[j] {
+            edits.extend(pending.take());
+            pos += a[i].len();
+            i += 1;
+            j += 1;
+        } else if i < n && (j == m || lcs[(i + 1) * (m + 1) + j] >= lcs[i * (m + 1) + j + 1]) {
+            let edit = pending.get_or_insert_with(|| StringEdit { range: pos..pos, replacement: String::new() });
+            pos += a[i].len();
+            edit.range.end = pos;
+            i += 1;
+        } else {
+            let edit = pending.get_or_insert_with(|| StringEdit { range: pos..pos, replacement: String::new() });
+            edit.replacement.push_str(b[j]);
+            j += 1;
+        }
+    }
+    edits.extend(pending);
+
+    edits
+}
+
+/// Splits `s` into runs of alphanumeric characters and single other characters.
+fn tokens(s: &str) -> Vec<&str> {
+    let mut tokens = vec![];
+    let mut start = 0;
+    for (idx, c) in s.char_indices() {
+        if !c.is_alphanumeric() {
+            if start < idx {
+                tokens.push(&s[start..idx]);
+            }
+            tokens.push(&s[idx..idx + c.len_utf8()]);
+            start = idx + c.len_utf8();
+        }
+    }
+    if start < s.len() {
+        tokens.push(&s[start..]);
+    }
+
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{string_edits, StringEdit};
+
+    #[test]
+    fn string_edits_apply() {
+        let cases = [
+            ("The quick brown fox", "The slow brown fox jumps"),
+            ("abc", "abc"),
+            ("", "new text"),
+            ("old text", ""),
+            ("Grüße, Welt!", "Grüße, schöne Welt."),
+        ];
+        for (source, target) in cases {
+            let mut patched = source.to_string();
+            for edit in string_edits(source, target).iter().rev() {
+                patched.replace_range(edit.range.clone(), &edit.replacement);
+            }
+            assert_eq!(patched, target);
+        }
+
+        assert_eq!(string_edits("The quick brown fox", "The slow brown fox jumps"), vec![
+            StringEdit { range: 4..9, replacement: "slow".to_string() },
+            StringEdit { range: 19..19, replacement: " jumps".to_string() },
+        ]);
+    }
+
+    #[test]
+    fn string_edits_long_strings() {
+        let words: Vec<_> = (0..20_000).map(|i| format!("w{}", i)).collect();
+        let source = words.join(" ");
+        let target = source.replacen("w1 ", "x1 ", 1).replacen("w19998", "x19998", 1);
+
+        let edits = string_edits(&source, &target);
+        let replacement = target[3..target.len() - 7].to_string();
+        assert_eq!(edits, vec![StringEdit { range: 3..source.len() - 7, replacement }]);
+    }
+}
@@ -0,0 +1,77 @@
+use serde_json::json;
+use crate::{Difference, LeafDifference, Path};
+
+impl Difference {
+    /// Renders the difference as human-readable text with one line per leaf difference
+    /// and its fully-qualified path, e.g.:
+    ///
+    /// ```text
+    /// ~ users.[1].animals.type: "dog" => "cat"
+    /// - age
+    /// + email: "x@y.com"
+    /// > tags.[3] => [0]
+    /// > address: ["city","zip"] => ["zip","city"]
+    /// ```
+    ///
+    /// `~` marks changed values, `-` entries that only `source` has,
+    /// `+` entries that only `target` has and `>` array elements moved to another index
+    /// or the keys of an object in another order.
+    pub fn to_text(&self) -> String {
+        self.flatten()
+            .iter()
+            .map(|(path, leaf)| match leaf {
+                LeafDifference::Scalar(scalar) => {
+                    format!("~ {}: {} => {}", display_path(path), scalar.source_value(), scalar.target_value())
+                }
+                LeafDifference::Type { source_value, target_value, .. } => {
+                    format!("~ {}: {} => {}", display_path(path), source_value, target_value)
+                }
+                LeafDifference::Missing(value) => format!("+ {}: {}", display_path(path), value),
+                LeafDifference::Extra(_) => format!("- {}", display_path(path)),
+                LeafDifference::Moved { target_index, .. } => format!("> {} => [{}]", display_path(path), target_index),
+                LeafDifference::Reordered(key_order) => {
+                    format!("> {}: {} => {}", display_path(path), json!(key_order.source), json!(key_order.target))
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+pub(crate) fn display_path(path: &Path) -> String {
+    if path.is_empty() {
+        "(root)".to_string()
+    } else {
+        path.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use crate::DiffBuilder;
+
+    #[test]
+    fn to_text() {
+        let diff = DiffBuilder::default()
+            .source(json!({"age": 31, "users": [{"animals": {"type": "dog"}}], "id": 1}))
+            .target(json!({"email": "x@y.com", "users": [{"animals": {"type": "cat"}}], "id": "1"}))
+            .build()
+            .unwrap()
+            .compare()
+            .unwrap();
+
+        assert_eq!(
+            diff.to_text(),
+            [
+                "- age",
+                "~ id: 1 => \"1\"",
+                "~ users.[0].animals.type: \"dog\" => \"cat\"",
+                "+ email: \"x@y.com\"",
+            ].join("\n")
+        );
+
+        let diff = DiffBuilder::default().source(json!(1)).target(json!(2)).build().unwrap().compare().unwrap();
+        assert_eq!(diff.to_text(), "~ (root): 1 => 2");
+    }
+}
@@ -0,0 +1,88 @@
+use std::fmt;
+use std::io::Write;
+use crate::{CompareError, Diff, Difference};
+
+/// An error returned by [`Diff::compare_to_writer`].
+#[derive(Debug)]
+pub enum WriteError {
+    /// The values can't be compared, see [`Diff::try_compare`].
+    Compare(CompareError),
+    /// The difference can't be serialized or written.
+    Serialization(serde_json::Error),
+}
+
+impl fmt::Display for WriteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WriteError::Compare(err) => write!(f, "values can't be compared: {}", err),
+            WriteError::Serialization(err) => write!(f, "difference can't be written: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for WriteError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            WriteError::Compare(err) => Some(err),
+            WriteError::Serialization(err) => Some(err),
+        }
+    }
+}
+
+impl Diff {
+    /// Compares `source` with `target` and writes the difference as JSON to `writer`, e.g. stdout of a CLI,
+    /// or `null` if they are equal. `pretty` indents the JSON like [`serde_json::to_writer_pretty`].
+    /// The writer isn't buffered or flushed. If [`DiffBuilder::annotate_paths`](crate::DiffBuilder::annotate_paths)
+    /// is set every difference has its `path`, see [`Difference::with_paths`](crate::Difference::with_paths).
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    /// use sjdiff::DiffBuilder;
+    ///
+    /// let mut out = vec![];
+    /// DiffBuilder::default()
+    ///     .source(json!({"a": 1}))
+    ///     .target(json!({"a": 1}))
+    ///     .build()
+    ///     .unwrap()
+    ///     .compare_to_writer(&mut out, false)
+    ///     .unwrap();
+    /// assert_eq!(out, b"null");
+    /// ```
+    pub fn compare_to_writer(self, writer: impl Write, pretty: bool) -> Result<(), WriteError> {
+        let difference = self.try_compare_ref().map_err(WriteError::Compare)?;
+        let result = match (self.annotate_paths, pretty) {
+            (true, true) => serde_json::to_writer_pretty(writer, &difference.as_ref().map(Difference::with_paths)),
+            (true, false) => serde_json::to_writer(writer, &difference.as_ref().map(Difference::with_paths)),
+            (false, true) => serde_json::to_writer_pretty(writer, &difference),
+            (false, false) => serde_json::to_writer(writer, &difference),
+        };
+
+        result.map_err(WriteError::Serialization)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use crate::{DiffBuilder, WriteError};
+
+    #[test]
+    fn compare_to_writer() {
+        let diff = DiffBuilder::default().source(json!({"a": 1})).target(json!({"a": 2})).build().unwrap();
+        let expected = serde_json::to_string_pretty(&diff.compare_ref()).unwrap();
+        let mut out = vec![];
+        diff.compare_to_writer(&mut out, true).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), expected);
+
+        let diff = DiffBuilder::default().max_depth(1).source(json!([[1]])).target(json!([[2]])).build().unwrap();
+        let err = diff.compare_to_writer(vec![], false).unwrap_err();
+        assert!(matches!(err, WriteError::Compare(_)), "{:?}", err);
+
+        let diff = DiffBuilder::default().annotate_paths(true).source(json!({"a": 1})).target(json!({"a": 2})).build().unwrap();
+        let mut out = vec![];
+        diff.compare_to_writer(&mut out, false).unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(value["different_entries"]["a"]["value_diff"]["path"], json!("/a"));
+    }
+}